@@ -1,5 +1,5 @@
 import { Request, Response } from 'express';
-import { fetchSymbolIsin, fetchSymbolInfo, fetchSymbolPrices} from '../services/generalService';
+import { fetchSymbolIsin, fetchSymbolInfo, fetchSymbolPrices, fetchSymbolActions } from '../services/generalService';
 
 export const getSymbolInfo = async (req: Request, res: Response) => {
     const { symbol } = req.params;
@@ -20,6 +20,12 @@ export const getSymbolPrices = async (req: Request, res: Response) => {
     res.json(prices);
 };
 
+export const getSymbolActions = async (req: Request, res: Response) => {
+    const { symbol } = req.params;
+    const actions = await fetchSymbolActions(symbol);
+    res.json(actions);
+};
+
 export const getSymbolAll = async (req: Request, res: Response) => {
     const { symbol } = req.params;
     const { from, until } = req.query;
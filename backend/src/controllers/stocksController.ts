@@ -1,5 +1,5 @@
 import { Request, Response } from 'express';
-import { fetchAllPriceHistories, fetchAllStockIds, fetchAllStocks, fetchStockById, fetchStockPrices } from '../services/stockService';
+import { fetchAllActions, fetchAllPriceHistories, fetchAllStockIds, fetchAllStocks, fetchStockActions, fetchStockById, fetchStockPrices } from '../services/stockService';
 
 export const getAllStocks = async (req: Request, res: Response) => {
     const stocks = await fetchAllStocks();
@@ -29,3 +29,14 @@ export const getAllPriceHistories = async (req: Request, res: Response) => {
     const data = await fetchAllPriceHistories(from as string, until as string);
     res.json(data);
 };
+
+export const getStockActions = async (req: Request, res: Response) => {
+    const { stockId } = req.params;
+    const actions = await fetchStockActions(stockId);
+    res.json(actions);
+};
+
+export const getAllActions = async (req: Request, res: Response) => {
+    const data = await fetchAllActions();
+    res.json(data);
+};
@@ -3,7 +3,8 @@ import {
     getSymbolInfo,
     getSymbolPrices,
     getSymbolIsin,
-    getSymbolAll
+    getSymbolAll,
+    getSymbolActions
 } from '../../controllers/generalController';
 
 const router = Router();
@@ -11,6 +12,7 @@ const router = Router();
 router.get('/:symbol/info', getSymbolInfo);
 router.get('/:symbol/prices', getSymbolPrices);          // ?from=2024-01-01&until=2024-06-01
 router.get('/:symbol/isin', getSymbolIsin);
+router.get('/:symbol/actions', getSymbolActions);
 router.get('/:symbol', getSymbolAll);
 
 export default router;
@@ -4,7 +4,9 @@ import {
     getAllStockIds,
     getStockPriceHistory,
     getAllPriceHistories,
-    getStockById
+    getStockById,
+    getStockActions,
+    getAllActions
 } from '../../controllers/stocksController';
 
 const router = Router();
@@ -12,7 +14,9 @@ const router = Router();
 router.get('/', getAllStocks);
 router.get('/ids', getAllStockIds);
 router.get('/prices', getAllPriceHistories);            // ?from=2024-01-01&until=2024-06-01
+router.get('/actions', getAllActions);
 router.get('/:stockId/prices', getStockPriceHistory);   // ?from=...&until=...
+router.get('/:stockId/actions', getStockActions);
 router.get('/:stockId', getStockById);
 
 export default router;
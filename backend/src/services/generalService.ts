@@ -1,32 +1,56 @@
 import { getConnection } from "../config/db";
-import { fetchStockById, fetchStockPrices } from "./stockService";
+import { fetchStockActions, fetchStockById, fetchStockPrices } from "./stockService";
 import { fetchIndexById, fetchIndexPrices } from "./indexService";
 
-export const fetchSymbolIsin = async (symbol: string) => {
-    const connection = await getConnection();
-    const [rows]: any = await connection.query('SELECT isin FROM stocks WHERE symbol = ? UNION SELECT isin FROM indexes WHERE symbol = ?', [symbol, symbol]);
-    await connection.end();
-    return rows[0];
-};
+const ISIN_PATTERN = /^[A-Z]{2}[A-Z0-9]{9}[0-9]$/;
 
-export const fetchSymbolInfo = async (symbol: string) => {
+// accepts a bare symbol, a symbol qualified by its market (XZAG:ADPL) or an ISIN,
+// bare symbols listed on more than one market have to be qualified
+export const resolveSymbol = async (symbol: string) => {
     const connection = await getConnection();
-    const [rows]: any = await connection.query(
-        `SELECT isin, 'stock' AS type FROM stocks WHERE symbol = ?
-         UNION
-         SELECT isin, 'index' AS type FROM indexes WHERE symbol = ?`,
-        [symbol, symbol]
-    );
+    let rows: any;
+    if (symbol.includes(':')) {
+        const [mic, bare] = symbol.split(':', 2);
+        [rows] = await connection.query(
+            `SELECT isin, mic, symbol, 'stock' AS type FROM stocks WHERE mic = ? AND symbol = ?
+             UNION
+             SELECT isin, mic, symbol, 'index' AS type FROM indexes WHERE mic = ? AND symbol = ?`,
+            [mic, bare, mic, bare]
+        );
+    } else if (ISIN_PATTERN.test(symbol)) {
+        [rows] = await connection.query(
+            `SELECT isin, mic, symbol, 'stock' AS type FROM stocks WHERE isin = ?
+             UNION
+             SELECT isin, mic, symbol, 'index' AS type FROM indexes WHERE isin = ?`,
+            [symbol, symbol]
+        );
+    } else {
+        [rows] = await connection.query(
+            `SELECT isin, mic, symbol, 'stock' AS type FROM stocks WHERE symbol = ?
+             UNION
+             SELECT isin, mic, symbol, 'index' AS type FROM indexes WHERE symbol = ?`,
+            [symbol, symbol]
+        );
+    }
+    await connection.end();
 
     if (rows.length === 0) {
-        await connection.end();
         throw new Error(`Symbol not found: ${symbol}`);
     }
+    if (rows.length > 1) {
+        const options = rows.map((r: any) => `${r.mic}:${r.symbol}`).join(', ');
+        throw new Error(`Ambiguous symbol ${symbol}, use one of: ${options}`);
+    }
+    return rows[0] as { isin: string, mic: string, symbol: string, type: 'stock' | 'index' };
+};
 
-    const isin = rows[0].isin;
-    const type = rows[0].type;
+export const fetchSymbolIsin = async (symbol: string) => {
+    const { isin } = await resolveSymbol(symbol);
+    return { isin };
+};
 
-    await connection.end();
+export const fetchSymbolInfo = async (symbol: string) => {
+    const { isin, type } = await resolveSymbol(symbol);
 
     if (type === 'stock') {
         let res = await fetchStockById(isin);
@@ -38,23 +62,7 @@ export const fetchSymbolInfo = async (symbol: string) => {
 }
 
 export const fetchSymbolPrices = async (symbol: string, from?: string, until?: string) => {
-    const connection = await getConnection();
-    const [rows]: any = await connection.query(
-        `SELECT isin, 'stock' AS type FROM stocks WHERE symbol = ?
-         UNION
-         SELECT isin, 'index' AS type FROM indexes WHERE symbol = ?`,
-        [symbol, symbol]
-    );
-
-    if (rows.length === 0) {
-        await connection.end();
-        throw new Error(`Symbol not found: ${symbol}`);
-    }
-
-    const isin = rows[0].isin;
-    const type = rows[0].type;
-
-    await connection.end();
+    const { isin, type } = await resolveSymbol(symbol);
 
     if (type === 'stock') {
         let res = await fetchStockPrices(isin, from, until);
@@ -63,4 +71,15 @@ export const fetchSymbolPrices = async (symbol: string, from?: string, until?: s
         let res = await fetchIndexPrices(isin, from, until);
         return res;
     }
-};
\ No newline at end of file
+};
+
+export const fetchSymbolActions = async (symbol: string) => {
+    const { isin, type } = await resolveSymbol(symbol);
+
+    // indexes have no corporate actions
+    if (type !== 'stock') {
+        return [];
+    }
+
+    return await fetchStockActions(isin);
+};
@@ -41,6 +41,30 @@ export const fetchStockPrices = async (stockId: string, from?: string, until?: s
     return rows;
 };
 
+export const fetchStockActions = async (stockId: string) => {
+    const connection = await getConnection();
+    const [rows] = await connection.query('SELECT * FROM corporate_actions WHERE stock_isin = ? ORDER BY ex_date ASC', [stockId]);
+    await connection.end();
+    return rows;
+};
+
+export const fetchAllActions = async () => {
+    const connection = await getConnection();
+    const [rows] = await connection.query('SELECT * FROM corporate_actions ORDER BY stock_isin, ex_date ASC');
+    await connection.end();
+
+    // Group rows by stock_isin
+    const grouped: Record<string, any[]> = {};
+    for (const row of rows as any[]) {
+        if (!grouped[row.stock_isin]) {
+            grouped[row.stock_isin] = [];
+        }
+        grouped[row.stock_isin].push(row);
+    }
+
+    return grouped;
+};
+
 export const fetchAllPriceHistories = async (from?: string, until?: string) => {
     const connection = await getConnection();
     const params: any[] = [];
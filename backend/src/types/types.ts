@@ -48,6 +48,17 @@ export interface DailyPrice {
     turnover_currency?: string;
 }
   
+export interface CorporateAction {
+    id: number;
+    stock_isin: string;
+    ex_date: string;
+    action_type: 'dividend' | 'split' | 'rights';
+    amount?: number;
+    ratio_old?: number;
+    ratio_new?: number;
+    currency?: string;
+}
+  
 export interface IndexValue {
     id: number;
     index_isin: string;
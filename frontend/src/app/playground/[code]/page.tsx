@@ -6,12 +6,26 @@ import { GenericSeries, VolumeSeriesInput } from '@/components/chart/GenericChar
 import MatchingItemsTable from '@/components/table/MatchingItemstable';
 import QueryInput from "@/components/ui/QueryInput";
 import { Item, Response } from '@/types/types.js';
-import { CandlestickData, HistogramData, LineData } from 'lightweight-charts';
+import { CandlestickData, HistogramData, LineData, SeriesMarker, Time } from 'lightweight-charts';
 import Link from "next/link.js";
 import { useParams } from "next/navigation";
 import { useEffect, useState } from "react";
+import type { EvalSession } from "../../../../public/wasm/evaluator_core.js";
 
 
+// one evaluation session for the page, later scripts reuse its listings, prices and indicators
+let sessionPromise: Promise<EvalSession> | null = null;
+
+function getSession(): Promise<EvalSession> {
+  if (!sessionPromise) {
+    sessionPromise = import("../../../../public/wasm/evaluator_core.js").then(async (wasm) => {
+      await wasm.default();
+      return new wasm.EvalSession(undefined);
+    });
+  }
+  return sessionPromise;
+}
+
 export default function PlaygroundQueryPage() {
   const { code } = useParams();
   const [decodedQuery, setDecodedQuery] = useState("");
@@ -34,11 +48,9 @@ export default function PlaygroundQueryPage() {
 
   useEffect(() => {
     async function loadWasm() {
-      const wasm = await import("../../../../public/wasm/evaluator_core.js");
-
-      await wasm.default();
+      const session = await getSession();
 
-      const res1 = await wasm.evaluate_script_wasm(decodedQuery);
+      const res1 = await session.evaluate(decodedQuery);
       console.log(res1);
       if (res1) {
         setResponse(res1);
@@ -89,6 +101,35 @@ export default function PlaygroundQueryPage() {
             title: chart.id,
             panel: chart.panel_id
           });
+        } else if (chart.chart_type == "Histogram") {
+          let histSeries: HistogramData[] = chart.data.map((x) => {return({time: x.date.split("T")[0], value: x.value[0], color: x.value[0] >= 0 ? "green" : "red"})});
+          chartSeries.push({
+            id: chart.id,
+            type: "histogram",
+            data: histSeries,
+            title: chart.id,
+            panel: chart.panel_id
+          });
+        } else if (chart.chart_type == "Marker") {
+          // value[1] is the side of the pattern, bullish ones go below the bar and bearish ones above
+          let markers: SeriesMarker<Time>[] = chart.data.filter((x) => x.value[0] == 1).map((x): SeriesMarker<Time> => {
+            const side = x.value[1];
+            return({
+              time: x.date.split("T")[0],
+              position: side > 0 ? "belowBar" : "aboveBar",
+              shape: side > 0 ? "arrowUp" : side < 0 ? "arrowDown" : "circle",
+              color: side > 0 ? "green" : side < 0 ? "red" : "#C3BCDB",
+              text: chart.key.function ?? chart.id
+            });
+          });
+          chartSeries.push({
+            id: chart.id,
+            type: "markers",
+            data: markers,
+            title: chart.id,
+            panel: chart.panel_id,
+            target: chart.key.item
+          });
         } else if (chart.chart_type == "Rebase") {
           let lineSeries: LineData[] = chart.data.map((x) => {return({time: x.date.split("T")[0], value: x.value[0]})});
           chartSeries.push({
@@ -8,6 +8,7 @@ import {
   HistogramSeries,
   CandlestickSeries,
   LineSeries,
+  createSeriesMarkers,
 } from "lightweight-charts";
 import useWindowDimensions from "@/hooks/useWindowDimensions";
 
@@ -16,9 +17,11 @@ import {
   CandlestickData,
   HistogramData,
   LineData,
+  ISeriesApi,
+  SeriesMarker,
 } from "lightweight-charts";
 
-export type ChartSeriesType = "candlestick" | "volume" | "line";
+export type ChartSeriesType = "candlestick" | "volume" | "line" | "histogram" | "markers";
 
 export interface BaseSeries<T> {
   id: string;
@@ -41,10 +44,22 @@ export type LineSeriesInput = BaseSeries<LineData> & {
   type: "line";
 };
 
+export type HistogramSeriesInput = BaseSeries<HistogramData> & {
+  type: "histogram";
+};
+
+export type MarkerSeriesInput = BaseSeries<SeriesMarker<Time>> & {
+  type: "markers";
+  // id of the candlestick series the markers are drawn on
+  target: string;
+};
+
 export type GenericSeries =
   | CandlestickSeriesInput
   | VolumeSeriesInput
-  | LineSeriesInput;
+  | LineSeriesInput
+  | HistogramSeriesInput
+  | MarkerSeriesInput;
 
 interface GenericChartProps {
   series: GenericSeries[];
@@ -114,6 +129,7 @@ export default function GenericChart({
       : 0;
 
     let added = false;
+    const candlesticks: Record<string, ISeriesApi<"Candlestick">> = {};
 
     // Create each series
     for (const s of series) {
@@ -131,6 +147,7 @@ export default function GenericChart({
           candlestickSeries.priceScale().applyOptions({
             autoScale: true,
           });
+          candlesticks[s.id] = candlestickSeries;
           break;
         }
 
@@ -186,6 +203,33 @@ export default function GenericChart({
           lineSeries.setData(s.data);
           break;
         }
+
+        case "histogram": {
+          const histogramSeries = chart.addSeries(HistogramSeries, {
+            title: s.title
+          }, s.panel);
+          histogramSeries.setData(s.data);
+          break;
+        }
+
+        // drawn on their candlesticks once all series exist
+        case "markers":
+          break;
+      }
+    }
+
+    // markers of all patterns of an item go on its candlesticks together, sorted by time
+    const markers: Record<string, SeriesMarker<Time>[]> = {};
+    for (const s of series) {
+      if (s.type === "markers") {
+        markers[s.target] = [...(markers[s.target] ?? []), ...s.data];
+      }
+    }
+    for (const [target, data] of Object.entries(markers)) {
+      const candlestickSeries = candlesticks[target];
+      if (candlestickSeries) {
+        data.sort((a, b) => String(a.time).localeCompare(String(b.time)));
+        createSeriesMarkers(candlestickSeries, data);
       }
     }
 
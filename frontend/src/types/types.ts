@@ -68,11 +68,21 @@ export interface Response {
 
 export interface Chart {
     id: string;
+    key: SeriesKey;
     panel_id: number;
     chart_type: string;
     data: ChartData[];
 }
 
+export interface SeriesKey {
+    item: string;
+    function?: string | null;
+    params: string[];
+    // hex hash of arithmetic expressions
+    expr_hash?: string | null;
+    output?: string | null;
+}
+
 export interface ChartData {
     date: string;
     value: number[];
@@ -1,6 +1,7 @@
 use std::env;
 use std::fs;
 use evaluator_core::evaluate_script;
+use evaluator_core::config::EvalConfig;
 use futures::executor::block_on;
 
 fn main() {
@@ -23,5 +24,5 @@ fn main() {
         "CHART(items=[ZVTG], from=2025-06-01, to=today)".to_string()
     };
 
-    block_on(evaluate_script(&input));
+    block_on(evaluate_script(&input, &EvalConfig::from_env()));
 }
@@ -0,0 +1,200 @@
+use crate::functions::indicator::{Point, Series};
+use crate::helpers::daily_prices_to_series;
+use crate::types::{CorporateAction, DailyPrice};
+
+// (price factor, volume factor) of a single action, relative to the close before the ex-date
+fn action_factors(action: &CorporateAction, prev_close: f64) -> (f64, f64) {
+    match action.action_type.as_str() {
+        "dividend" => {
+            let amount = action.amount.unwrap_or(0.0);
+            if prev_close <= 0.0 || amount >= prev_close {
+                return (1.0, 1.0);
+            }
+            ((prev_close - amount) / prev_close, 1.0)
+        }
+        "split" => match (action.ratio_old, action.ratio_new) {
+            (Some(old), Some(new)) if old > 0.0 && new > 0.0 => (old / new, new / old),
+            _ => (1.0, 1.0),
+        },
+        "rights" => match (action.ratio_old, action.ratio_new, action.amount) {
+            // theoretical ex-rights price: (held * close + offered * subscription) / (held + offered)
+            (Some(held), Some(offered), Some(subscription)) if held > 0.0 && prev_close > 0.0 => {
+                let terp = (held * prev_close + offered * subscription) / (held + offered);
+                (terp / prev_close, 1.0)
+            }
+            _ => (1.0, 1.0),
+        },
+        _ => (1.0, 1.0),
+    }
+}
+
+/// Cumulative backward adjustment factors for each bar of a date sorted series.
+/// Bars before an ex-date get multiplied by the factors of all later actions,
+/// so the latest bar always keeps its raw price.
+pub fn adjustment_factors(series: &[Point], actions: &[CorporateAction]) -> Vec<(f64, f64)> {
+    let mut factors = vec![(1.0, 1.0); series.len()];
+
+    for action in actions {
+        // first bar trading ex the action, nothing to adjust if it is the first bar or after the series
+        let ex_idx = match series.iter().position(|(date, _)| date.as_str() >= action.ex_date.as_str()) {
+            Some(idx) if idx > 0 => idx,
+            _ => continue,
+        };
+        let prev_close = series[ex_idx - 1].1.0;
+        let (price_factor, volume_factor) = action_factors(action, prev_close);
+        for f in factors.iter_mut().take(ex_idx) {
+            f.0 *= price_factor;
+            f.1 *= volume_factor;
+        }
+    }
+
+    factors
+}
+
+/// Adjusted OHLC history: dividends, splits and rights issues applied backwards.
+/// Volumes are only scaled by splits, turnover stays as traded.
+pub fn adjust_daily_prices(prices: &[DailyPrice], actions: &[CorporateAction]) -> Vec<DailyPrice> {
+    if actions.is_empty() {
+        return prices.to_vec();
+    }
+
+    let mut sorted = prices.to_vec();
+    sorted.sort_by(|a, b| a.date.cmp(&b.date));
+    let factors = adjustment_factors(&daily_prices_to_series(&sorted), actions);
+
+    sorted
+        .into_iter()
+        .zip(factors)
+        .map(|(mut p, (price_factor, volume_factor))| {
+            p.open_price = p.open_price.map(|v| v * price_factor);
+            p.high_price = p.high_price.map(|v| v * price_factor);
+            p.low_price = p.low_price.map(|v| v * price_factor);
+            p.last_price = p.last_price.map(|v| v * price_factor);
+            p.vwap_price = p.vwap_price.map(|v| v * price_factor);
+            p.volume = p.volume.map(|v| v * volume_factor);
+            p
+        })
+        .collect()
+}
+
+/// Total-return series with dividends reinvested on the ex-date. It starts at the first
+/// close, so it can be drawn over the raw prices. Pass no actions for already adjusted series.
+pub fn total_return(series: &[Point], actions: &[CorporateAction]) -> Series {
+    let factors = adjustment_factors(series, actions);
+    let base = match factors.first() {
+        Some((f, _)) if *f != 0.0 => *f,
+        _ => return vec![],
+    };
+
+    series
+        .iter()
+        .zip(factors)
+        .map(|((date, v), (price_factor, _))| {
+            let scale = price_factor / base;
+            (date.clone(), (v.0 * scale, v.1 * scale, v.2 * scale, v.3 * scale))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(ex_date: &str, action_type: &str, amount: Option<f64>, ratio: Option<(f64, f64)>) -> CorporateAction {
+        CorporateAction {
+            id: 0,
+            stock_isin: "HRKRKGRA0003".to_string(),
+            ex_date: ex_date.to_string(),
+            action_type: action_type.to_string(),
+            amount,
+            ratio_old: ratio.map(|r| r.0),
+            ratio_new: ratio.map(|r| r.1),
+            currency: None,
+        }
+    }
+
+    fn price(date: &str, close: f64, volume: f64) -> DailyPrice {
+        DailyPrice {
+            id: 0,
+            stock_isin: "HRKRKGRA0003".to_string(),
+            date: date.to_string(),
+            trading_model_id: None,
+            open_price: Some(close),
+            high_price: Some(close + 1.0),
+            low_price: Some(close - 1.0),
+            last_price: Some(close),
+            vwap_price: None,
+            change_prev_close_percentage: None,
+            num_trades: None,
+            volume: Some(volume),
+            turnover: Some(close * volume),
+            price_currency: None,
+            turnover_currency: None,
+        }
+    }
+
+    fn assert_factors(factors: (f64, f64), expected: (f64, f64)) {
+        assert!((factors.0 - expected.0).abs() < 1e-12 && (factors.1 - expected.1).abs() < 1e-12, "{:?} != {:?}", factors, expected);
+    }
+
+    #[test]
+    fn dividend_factor_removes_the_amount_from_the_previous_close() {
+        assert_factors(action_factors(&action("2024-05-02", "dividend", Some(2.0), None), 100.0), (0.98, 1.0));
+        // a dividend at or above the price is bad data, not adjusted
+        assert_factors(action_factors(&action("2024-05-02", "dividend", Some(120.0), None), 100.0), (1.0, 1.0));
+        assert_factors(action_factors(&action("2024-05-02", "dividend", Some(2.0), None), 0.0), (1.0, 1.0));
+    }
+
+    #[test]
+    fn split_factor_scales_prices_and_volumes() {
+        assert_factors(action_factors(&action("2024-05-02", "split", None, Some((1.0, 2.0))), 100.0), (0.5, 2.0));
+        // reverse split 10:1
+        assert_factors(action_factors(&action("2024-05-02", "split", None, Some((10.0, 1.0))), 100.0), (10.0, 0.1));
+        assert_factors(action_factors(&action("2024-05-02", "split", None, Some((0.0, 2.0))), 100.0), (1.0, 1.0));
+    }
+
+    #[test]
+    fn rights_factor_uses_the_theoretical_ex_rights_price() {
+        // 1 new share at 50 for every 4 held at 100: TERP (4 * 100 + 50) / 5 = 90
+        assert_factors(action_factors(&action("2024-05-02", "rights", Some(50.0), Some((4.0, 1.0))), 100.0), (0.9, 1.0));
+        assert_factors(action_factors(&action("2024-05-02", "rights", None, Some((4.0, 1.0))), 100.0), (1.0, 1.0));
+    }
+
+    #[test]
+    fn adjust_daily_prices_applies_later_actions_backwards() {
+        let prices = vec![
+            price("2024-05-06", 49.0, 10.0),
+            price("2024-05-02", 100.0, 10.0),
+            price("2024-05-03", 50.0, 20.0),
+        ];
+        let actions = vec![
+            action("2024-05-03", "split", None, Some((1.0, 2.0))),
+            action("2024-05-06", "dividend", Some(1.0), None),
+            // before the first bar, nothing to adjust
+            action("2024-01-02", "dividend", Some(5.0), None),
+        ];
+        let adjusted = adjust_daily_prices(&prices, &actions);
+
+        let dates: Vec<&str> = adjusted.iter().map(|p| p.date.as_str()).collect();
+        assert_eq!(dates, vec!["2024-05-02", "2024-05-03", "2024-05-06"]);
+        // split halves the first bar, the dividend takes 1 of the 50 close before its ex-date
+        let closes: Vec<f64> = adjusted.iter().map(|p| p.last_price.unwrap()).collect();
+        for (close, expected) in closes.iter().zip([49.0, 49.0, 49.0]) {
+            assert!((close - expected).abs() < 1e-9, "{:?}", closes);
+        }
+        assert!((adjusted[0].high_price.unwrap() - 101.0 * 0.49).abs() < 1e-9);
+        assert_eq!(adjusted[0].volume, Some(20.0));
+        assert_eq!(adjusted[1].volume, Some(20.0));
+        // turnover stays as traded
+        assert_eq!(adjusted[0].turnover, Some(1000.0));
+        assert_eq!(adjusted[2].last_price, Some(49.0));
+    }
+
+    #[test]
+    fn total_return_reinvests_dividends_from_the_first_close() {
+        let series = daily_prices_to_series(&[price("2024-05-02", 100.0, 1.0), price("2024-05-03", 98.0, 1.0)]);
+        let tr = total_return(&series, &[action("2024-05-03", "dividend", Some(2.0), None)]);
+        assert_eq!(tr[0].1.0, 100.0);
+        assert!((tr[1].1.0 - 100.0).abs() < 1e-9);
+    }
+}
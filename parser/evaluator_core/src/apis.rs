@@ -1,22 +1,21 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, error::Error};
-use gloo_net::http::Request;
-use crate::types::{Index, Stock, DailyPrice, IndexValue};
+use crate::types::{CorporateAction, Index, Stock, DailyPrice, IndexValue};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum ApiResponse {
     Stock(StockResponse),
     Index(IndexResponse),
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StockResponse {
     pub info: Stock,
     pub prices: Vec<DailyPrice>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct IndexResponse {
     pub info: Index,
     pub prices: Vec<IndexValue>,
@@ -24,17 +23,180 @@ pub struct IndexResponse {
 
 
 
+pub const DEFAULT_BASE_URL: &str = "https://monitor-api.tijan.dev/api";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub base_url: String,
+    pub auth_header: Option<AuthHeader>,
+    pub endpoints: Endpoints,
+    pub timeout_secs: u64,
+    pub retries: u32,
+    // max number of requests in flight when fetching many symbols at once
+    pub max_concurrency: usize,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            auth_header: None,
+            endpoints: Endpoints::default(),
+            timeout_secs: 30,
+            retries: 2,
+            max_concurrency: 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthHeader {
+    pub name: String,
+    pub value: String,
+}
+
+// paths relative to the base url, {symbol} is replaced with the requested symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Endpoints {
+    pub symbol: String,
+    pub symbol_actions: String,
+    pub stocks: String,
+    pub stocks_prices: String,
+    pub stocks_actions: String,
+    pub indexes: String,
+    pub indexes_prices: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            symbol: "general/{symbol}".to_string(),
+            symbol_actions: "general/{symbol}/actions".to_string(),
+            stocks: "stocks".to_string(),
+            stocks_prices: "stocks/prices".to_string(),
+            stocks_actions: "stocks/actions".to_string(),
+            indexes: "indexes".to_string(),
+            indexes_prices: "indexes/prices".to_string(),
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Default config overridden by MONITOR_API_URL, MONITOR_API_TIMEOUT and MONITOR_API_RETRIES,
+    /// used by server-side jobs and to point native runs at a local server.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(url) = std::env::var("MONITOR_API_URL") {
+            config.base_url = url.trim_end_matches('/').to_string();
+        }
+        if let Some(timeout) = std::env::var("MONITOR_API_TIMEOUT").ok().and_then(|v| v.parse().ok()) {
+            config.timeout_secs = timeout;
+        }
+        if let Some(retries) = std::env::var("MONITOR_API_RETRIES").ok().and_then(|v| v.parse().ok()) {
+            config.retries = retries;
+        }
+        config
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+    }
+
+    fn symbol_url(&self, path: &str, symbol: &str) -> String {
+        self.url(&path.replace("{symbol}", symbol))
+    }
+}
+
+
+// only server errors are worth retrying, a 4xx answer stays the same
+fn retryable_status(status: u16) -> bool {
+    status >= 500
+}
+
 // WASM IMPLEMENTATION
-pub async fn fetch_api_data_async(symbol: &str, from: &str, to: &str) -> Result<ApiResponse, Box<dyn Error>> {
-    let url = format!("https://monitor-api.tijan.dev/api/general/{}?from={}&to={}", symbol, from, to);
+// timeouts are left to the browser, failed requests and server errors are retried with the native backoff
+#[cfg(target_arch = "wasm32")]
+async fn get_text(config: &ApiConfig, url: &str) -> Result<String, Box<dyn Error>> {
+    use gloo_net::http::Request;
+
+    let mut attempt = 0;
+    loop {
+        let mut req = Request::get(url);
+        if let Some(auth) = &config.auth_header {
+            req = req.header(&auth.name, &auth.value);
+        }
+        let (res, retryable) = match req.send().await {
+            Ok(res) if !res.ok() => (Err(boxed(&format!("Request error: {} returned {}", url, res.status()))), retryable_status(res.status())),
+            Ok(res) => (res.text().await.map_err(|e| boxed(&format!("Read body error: {}", e))), true),
+            Err(e) => (Err(boxed(&format!("Request error: {}", e))), true),
+        };
+        match res {
+            Ok(text) => return Ok(text),
+            Err(e) if !retryable || attempt >= config.retries => return Err(e),
+            Err(_) => {
+                gloo_timers::future::sleep(retry_delay(attempt)).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// NATIVE IMPLEMENTATION
+// ureq blocks, so every request runs on a thread of its own and the future only waits for its
+// result, requests polled together (the prefetch) are in flight at the same time on any executor
+#[cfg(not(target_arch = "wasm32"))]
+async fn get_text(config: &ApiConfig, url: &str) -> Result<String, Box<dyn Error>> {
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    let (config, url) = (config.clone(), url.to_string());
+    std::thread::spawn(move || {
+        let _ = sender.send(get_text_blocking(&config, &url));
+    });
+    match receiver.await {
+        Ok(res) => res.map_err(|e| boxed(&e)),
+        Err(_) => Err(boxed("Request error: request thread stopped")),
+    }
+}
+
+// transport errors and server errors are retried after a delay that doubles with every attempt
+#[cfg(not(target_arch = "wasm32"))]
+fn get_text_blocking(config: &ApiConfig, url: &str) -> Result<String, String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(config.timeout_secs))
+        .build();
+
+    let mut attempt = 0;
+    loop {
+        let mut req = agent.get(url);
+        if let Some(auth) = &config.auth_header {
+            req = req.set(&auth.name, &auth.value);
+        }
+        let (res, retryable) = match req.call() {
+            Ok(res) => (res.into_string().map_err(|e| format!("Read body error: {}", e)), true),
+            Err(ureq::Error::Status(status, _)) => (Err(format!("Request error: {} returned {}", url, status)), retryable_status(status)),
+            Err(e) => (Err(format!("Request error: {}", e)), true),
+        };
+        match res {
+            Ok(text) => return Ok(text),
+            Err(e) if !retryable || attempt >= config.retries => return Err(e),
+            Err(_) => {
+                std::thread::sleep(retry_delay(attempt));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// 250ms before the first retry, at most 8s
+fn retry_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(250 << attempt.min(5))
+}
 
-    let text = Request::get(&url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
+pub async fn fetch_api_data_async(config: &ApiConfig, symbol: &str, from: &str, to: &str) -> Result<ApiResponse, Box<dyn Error>> {
+    let url = format!("{}?from={}&to={}", config.symbol_url(&config.endpoints.symbol, symbol), from, to);
+    let text = get_text(config, &url).await?;
 
     let response: ApiResponse = serde_json::from_str(&text)
         .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
@@ -42,16 +204,9 @@ pub async fn fetch_api_data_async(symbol: &str, from: &str, to: &str) -> Result<
     Ok(response)
 }
 
-pub async fn fetch_all_stocks() -> Result<Vec<Stock>, Box<dyn Error>> {
-    let url = "https://monitor-api.tijan.dev/api/stocks";
-
-    let text = Request::get(url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
+pub async fn fetch_all_stocks(config: &ApiConfig) -> Result<Vec<Stock>, Box<dyn Error>> {
+    let url = config.url(&config.endpoints.stocks);
+    let text = get_text(config, &url).await?;
 
     let stocks: Vec<Stock> = serde_json::from_str(&text)
         .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
@@ -59,16 +214,9 @@ pub async fn fetch_all_stocks() -> Result<Vec<Stock>, Box<dyn Error>> {
     Ok(stocks)
 }
 
-pub async fn fetch_all_indexes() -> Result<Vec<Index>, Box<dyn Error>> {
-    let url = "https://monitor-api.tijan.dev/api/indexes";
-
-    let text = Request::get(url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
+pub async fn fetch_all_indexes(config: &ApiConfig) -> Result<Vec<Index>, Box<dyn Error>> {
+    let url = config.url(&config.endpoints.indexes);
+    let text = get_text(config, &url).await?;
 
     let indexes: Vec<Index> = serde_json::from_str(&text)
         .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
@@ -76,16 +224,9 @@ pub async fn fetch_all_indexes() -> Result<Vec<Index>, Box<dyn Error>> {
     Ok(indexes)
 }
 
-pub async fn fetch_all_indexes_prices(from: &str, to: &str) -> Result<HashMap<String, Vec<IndexValue>>, Box<dyn Error>> {
-    let url = format!("https://monitor-api.tijan.dev/api/indexes/prices?from={}&to={}", from, to);
-
-    let text = Request::get(&url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
+pub async fn fetch_all_indexes_prices(config: &ApiConfig, from: &str, to: &str) -> Result<HashMap<String, Vec<IndexValue>>, Box<dyn Error>> {
+    let url = format!("{}?from={}&to={}", config.url(&config.endpoints.indexes_prices), from, to);
+    let text = get_text(config, &url).await?;
 
     let indexes: HashMap<String, Vec<IndexValue>> = serde_json::from_str(&text)
         .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
@@ -93,16 +234,9 @@ pub async fn fetch_all_indexes_prices(from: &str, to: &str) -> Result<HashMap<St
     Ok(indexes)
 }
 
-pub async fn fetch_all_stocks_prices(from: &str, to: &str) -> Result<HashMap<String, Vec<DailyPrice>>, Box<dyn Error>> {
-    let url = format!("https://monitor-api.tijan.dev/api/stocks/prices?from={}&to={}", from, to);
-
-    let text = Request::get(&url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
+pub async fn fetch_all_stocks_prices(config: &ApiConfig, from: &str, to: &str) -> Result<HashMap<String, Vec<DailyPrice>>, Box<dyn Error>> {
+    let url = format!("{}?from={}&to={}", config.url(&config.endpoints.stocks_prices), from, to);
+    let text = get_text(config, &url).await?;
 
     let stocks: HashMap<String, Vec<DailyPrice>> = serde_json::from_str(&text)
         .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
@@ -110,63 +244,128 @@ pub async fn fetch_all_stocks_prices(from: &str, to: &str) -> Result<HashMap<Str
     Ok(stocks)
 }
 
+pub async fn fetch_corporate_actions(config: &ApiConfig, symbol: &str) -> Result<Vec<CorporateAction>, Box<dyn Error>> {
+    let url = config.symbol_url(&config.endpoints.symbol_actions, symbol);
+    let text = get_text(config, &url).await?;
 
-/*
-// NATIVE IMPLEMENTATION
-pub async fn fetch_api_data_async(symbol: &str, from: &str, to: &str) -> Result<ApiResponse, Box<dyn Error>> {
-    let url = format!("https://monitor-api.tijan.dev/api/general/{}?from={}&to={}", symbol, from, to);
-
-    let text = Request::get(&url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
-
-    let response: ApiResponse = serde_json::from_str(&text)
+    let actions: Vec<CorporateAction> = serde_json::from_str(&text)
         .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
 
-    Ok(response)
+    Ok(actions)
 }
 
-pub async fn fetch_all_stocks() -> Result<Vec<Stock>, Box<dyn Error>> {
-    let url = "https://monitor-api.tijan.dev/api/stocks";
-
-    let text = Request::get(url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
+pub async fn fetch_all_corporate_actions(config: &ApiConfig) -> Result<HashMap<String, Vec<CorporateAction>>, Box<dyn Error>> {
+    let url = config.url(&config.endpoints.stocks_actions);
+    let text = get_text(config, &url).await?;
 
-    let stocks: Vec<Stock> = serde_json::from_str(&text)
+    let actions: HashMap<String, Vec<CorporateAction>> = serde_json::from_str(&text)
         .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
 
-    Ok(stocks)
+    Ok(actions)
 }
 
-pub async fn fetch_all_indexes() -> Result<Vec<Index>, Box<dyn Error>> {
-    let url = "https://monitor-api.tijan.dev/api/indexes";
-
-    let text = Request::get(url)
-        .send()
-        .await
-        .map_err(|e| boxed(&format!("Request error: {}", e)))?
-        .text()
-        .await
-        .map_err(|e| boxed(&format!("Read body error: {}", e)))?;
-
-    let indexes: Vec<Index> = serde_json::from_str(&text)
-        .map_err(|e| boxed(&format!("JSON error: {}", e)))?;
-
-    Ok(indexes)
-}
-*/
-
 
 /// Helper to convert strings into Box<dyn Error>
 fn boxed(msg: &str) -> Box<dyn Error> {
     msg.to_string().into()
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    // local server answering the queued (status, body) responses in order, one per connection,
+    // returns its base url and the head of every request it received
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/api", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    head.extend_from_slice(&buf[..n]);
+                }
+                received.lock().unwrap().push(String::from_utf8_lossy(&head).to_lowercase());
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (base_url, requests)
+    }
+
+    fn config(base_url: &str, retries: u32) -> ApiConfig {
+        ApiConfig { base_url: base_url.to_string(), timeout_secs: 5, retries, ..Default::default() }
+    }
+
+    #[test]
+    fn server_errors_are_retried_until_success() {
+        let (base_url, requests) = serve(vec![(500, ""), (503, ""), (200, "ok")]);
+        let config = config(&base_url, 2);
+        let text = block_on(get_text(&config, &config.url("stocks"))).unwrap();
+        assert_eq!(text, "ok");
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn retries_stop_after_the_configured_number() {
+        let (base_url, requests) = serve(vec![(500, ""), (500, ""), (200, "ok")]);
+        let config = config(&base_url, 1);
+        let err = block_on(get_text(&config, &config.url("stocks"))).unwrap_err();
+        assert!(err.to_string().contains("500"), "{}", err);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let (base_url, requests) = serve(vec![(404, ""), (200, "ok")]);
+        let config = config(&base_url, 2);
+        let err = block_on(get_text(&config, &config.url("general/NONE"))).unwrap_err();
+        assert!(err.to_string().contains("404"), "{}", err);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn auth_header_is_sent_to_the_endpoint_under_the_base_url() {
+        let (base_url, requests) = serve(vec![(200, "ok")]);
+        let config = ApiConfig {
+            auth_header: Some(AuthHeader { name: "X-Api-Key".to_string(), value: "secret".to_string() }),
+            ..config(&base_url, 0)
+        };
+        block_on(get_text(&config, &config.symbol_url(&config.endpoints.symbol, "KRKG"))).unwrap();
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("get /api/general/krkg http/1.1"), "{}", request);
+        assert!(request.contains("\r\nx-api-key: secret\r\n"), "{}", request);
+    }
+
+    #[test]
+    fn slow_answers_time_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        // accepts and never answers
+        std::thread::spawn(move || {
+            let _connection = listener.accept().unwrap();
+            std::thread::sleep(std::time::Duration::from_secs(5));
+        });
+        let config = ApiConfig { timeout_secs: 1, ..config(&base_url, 0) };
+        let started = std::time::Instant::now();
+        assert!(block_on(get_text(&config, &config.url("stocks"))).is_err());
+        assert!(started.elapsed() < std::time::Duration::from_secs(4));
+    }
+}
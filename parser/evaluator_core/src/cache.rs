@@ -0,0 +1,404 @@
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::future::{ready, Future};
+use std::pin::Pin;
+use std::rc::Rc;
+use chrono::{Duration, NaiveDate};
+use crate::apis::{fetch_api_data_async, ApiConfig, ApiResponse};
+use crate::helpers::get_today;
+
+pub type CacheFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Raw API history of one symbol and the date range it covers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedHistory {
+    pub from: String,
+    pub to: String,
+    // day of the last fetch of the range end, bars of that day can still change with the daily update
+    pub fetched_on: String,
+    pub response: ApiResponse,
+}
+
+/// Storage of cached price histories. Methods return boxed futures so async
+/// backends (e.g. IndexedDB through wasm-bindgen) can implement it too.
+pub trait PriceCache: std::fmt::Debug {
+    fn get<'a>(&'a self, symbol: &'a str) -> CacheFuture<'a, Option<CachedHistory>>;
+    fn put<'a>(&'a self, symbol: &'a str, history: CachedHistory) -> CacheFuture<'a, ()>;
+    fn remove<'a>(&'a self, symbol: &'a str) -> CacheFuture<'a, ()>;
+    fn clear(&self) -> CacheFuture<'_, ()>;
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    histories: RefCell<HashMap<String, CachedHistory>>,
+}
+
+impl PriceCache for MemoryCache {
+    fn get<'a>(&'a self, symbol: &'a str) -> CacheFuture<'a, Option<CachedHistory>> {
+        Box::pin(ready(self.histories.borrow().get(symbol).cloned()))
+    }
+
+    fn put<'a>(&'a self, symbol: &'a str, history: CachedHistory) -> CacheFuture<'a, ()> {
+        self.histories.borrow_mut().insert(symbol.to_string(), history);
+        Box::pin(ready(()))
+    }
+
+    fn remove<'a>(&'a self, symbol: &'a str) -> CacheFuture<'a, ()> {
+        self.histories.borrow_mut().remove(symbol);
+        Box::pin(ready(()))
+    }
+
+    fn clear(&self) -> CacheFuture<'_, ()> {
+        self.histories.borrow_mut().clear();
+        Box::pin(ready(()))
+    }
+}
+
+/// One JSON file per symbol in `dir`, shared between native runs.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DiskCache {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, symbol: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{}.json", symbol.replace([':', '/', '\\'], "_")))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PriceCache for DiskCache {
+    fn get<'a>(&'a self, symbol: &'a str) -> CacheFuture<'a, Option<CachedHistory>> {
+        let path = self.path(symbol);
+        let history = std::fs::read_to_string(&path).ok().and_then(|text| match serde_json::from_str(&text) {
+            Ok(history) => Some(history),
+            // corrupt or of an older format, fetched again
+            Err(err) => {
+                eprintln!("Invalid cache file for {}, removed it: {}", symbol, err);
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        });
+        Box::pin(ready(history))
+    }
+
+    fn put<'a>(&'a self, symbol: &'a str, history: CachedHistory) -> CacheFuture<'a, ()> {
+        let res = std::fs::create_dir_all(&self.dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string(&history).map_err(|e| e.to_string()))
+            .and_then(|text| std::fs::write(self.path(symbol), text).map_err(|e| e.to_string()));
+        if let Err(err) = res {
+            eprintln!("Failed to write cache for {}: {}", symbol, err);
+        }
+        Box::pin(ready(()))
+    }
+
+    fn remove<'a>(&'a self, symbol: &'a str) -> CacheFuture<'a, ()> {
+        let _ = std::fs::remove_file(self.path(symbol));
+        Box::pin(ready(()))
+    }
+
+    fn clear(&self) -> CacheFuture<'_, ()> {
+        if let Ok(entries) = std::fs::read_dir(&self.dir) {
+            for entry in entries.flatten() {
+                if entry.path().extension().is_some_and(|ext| ext == "json") {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+        Box::pin(ready(()))
+    }
+}
+
+thread_local! {
+    static SHARED_MEMORY_CACHE: Rc<MemoryCache> = Rc::new(MemoryCache::default());
+}
+
+/// In-memory cache shared by all evaluations on this thread (the WASM default).
+pub fn shared_memory_cache() -> Rc<MemoryCache> {
+    SHARED_MEMORY_CACHE.with(|cache| cache.clone())
+}
+
+// entry dates are checked with `valid_entry` before they are shifted
+fn shift_date(date: &str, days: i64) -> String {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").expect("cache entry dates are checked before use");
+    (date + Duration::days(days)).format("%Y-%m-%d").to_string()
+}
+
+// a corrupt entry or one of an older format is refetched instead of used
+fn valid_entry(entry: &CachedHistory) -> bool {
+    [&entry.from, &entry.to, &entry.fetched_on]
+        .iter()
+        .all(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+}
+
+// newer rows replace older ones on the same date, info always comes from the newer response
+fn merge_responses(older: ApiResponse, newer: ApiResponse) -> ApiResponse {
+    match (older, newer) {
+        (ApiResponse::Stock(mut old), ApiResponse::Stock(new)) => {
+            old.prices.retain(|p| !new.prices.iter().any(|n| n.date == p.date));
+            old.prices.extend(new.prices);
+            old.prices.sort_by(|a, b| a.date.cmp(&b.date));
+            old.info = new.info;
+            ApiResponse::Stock(old)
+        }
+        (ApiResponse::Index(mut old), ApiResponse::Index(new)) => {
+            old.prices.retain(|p| !new.prices.iter().any(|n| n.date == p.date));
+            old.prices.extend(new.prices);
+            old.prices.sort_by(|a, b| a.date.cmp(&b.date));
+            old.info = new.info;
+            ApiResponse::Index(old)
+        }
+        // symbol changed its type, nothing to merge
+        (_, newer) => newer,
+    }
+}
+
+fn slice_response(response: &ApiResponse, from: &str, to: &str) -> ApiResponse {
+    let mut response = response.clone();
+    match &mut response {
+        ApiResponse::Stock(res) => res.prices.retain(|p| p.date.as_str() >= from && p.date.as_str() <= to),
+        ApiResponse::Index(res) => res.prices.retain(|p| p.date.as_str() >= from && p.date.as_str() <= to),
+    }
+    response
+}
+
+/// `fetch_api_data_async` through a cache, only the date ranges missing from the cached
+/// history are requested. The last cached day is refetched once the day it was fetched on has passed.
+pub async fn fetch_cached(
+    cache: &dyn PriceCache,
+    config: &ApiConfig,
+    symbol: &str,
+    from: &str,
+    to: &str,
+) -> Result<ApiResponse, Box<dyn Error>> {
+    let fetch = |from: String, to: String| async move { fetch_api_data_async(config, symbol, &from, &to).await };
+    fetch_through_cache(cache, symbol, from, to, &get_today(), fetch).await
+}
+
+// `fetch` requests the history of `symbol` between two dates
+async fn fetch_through_cache<F, Fut>(
+    cache: &dyn PriceCache,
+    symbol: &str,
+    from: &str,
+    to: &str,
+    today: &str,
+    fetch: F,
+) -> Result<ApiResponse, Box<dyn Error>>
+where
+    F: Fn(String, String) -> Fut,
+    Fut: Future<Output = Result<ApiResponse, Box<dyn Error>>>,
+{
+    let entry = match cache.get(symbol).await {
+        Some(entry) if valid_entry(&entry) => entry,
+        cached => {
+            if cached.is_some() {
+                eprintln!("Invalid cache entry for {}, fetching it again", symbol);
+                cache.remove(symbol).await;
+            }
+            let response = fetch(from.to_string(), to.to_string()).await?;
+            cache.put(symbol, CachedHistory {
+                from: from.to_string(),
+                to: to.to_string(),
+                fetched_on: today.to_string(),
+                response: response.clone(),
+            }).await;
+            return Ok(response);
+        }
+    };
+
+    let covered_to = if entry.fetched_on == today {
+        entry.to.clone()
+    } else {
+        entry.to.clone().min(shift_date(&entry.fetched_on, -1))
+    };
+
+    let mut updated = entry.clone();
+    let mut changed = false;
+
+    if from < entry.from.as_str() {
+        let head = fetch(from.to_string(), shift_date(&entry.from, -1)).await?;
+        updated.response = merge_responses(updated.response, head);
+        updated.from = from.to_string();
+        changed = true;
+    }
+
+    if to > covered_to.as_str() {
+        let tail = fetch(shift_date(&covered_to, 1), to.to_string()).await?;
+        updated.response = merge_responses(updated.response, tail);
+        updated.to = to.to_string();
+        updated.fetched_on = today.to_string();
+        changed = true;
+    }
+
+    if changed {
+        cache.put(symbol, updated.clone()).await;
+    }
+
+    Ok(slice_response(&updated.response, from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use crate::apis::IndexResponse;
+    use crate::types::{Index, IndexValue};
+
+    // one bar per day between the dates, its value is the number of the fetch it came from
+    fn index_response(from: &str, to: &str, fetch: f64) -> ApiResponse {
+        let (from, to) = (NaiveDate::parse_from_str(from, "%Y-%m-%d").unwrap(), NaiveDate::parse_from_str(to, "%Y-%m-%d").unwrap());
+        let prices = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| IndexValue {
+                id: 0,
+                index_isin: "SI0026109882".to_string(),
+                date: date.format("%Y-%m-%d").to_string(),
+                open_value: None,
+                high_value: None,
+                low_value: None,
+                last_value: Some(fetch),
+                change_prev_close_percentage: None,
+                turnover: None,
+            })
+            .collect();
+        let info = Index {
+            isin: "SI0026109882".to_string(),
+            mic: "XLJU".to_string(),
+            symbol: "SBITOP".to_string(),
+            name: None,
+            last_value: None,
+            change_prev_close_percentage: None,
+        };
+        ApiResponse::Index(IndexResponse { info, prices })
+    }
+
+    // (date, fetch) of every bar
+    fn bars(response: &ApiResponse) -> Vec<(String, f64)> {
+        match response {
+            ApiResponse::Index(res) => res.prices.iter().map(|p| (p.date.clone(), p.last_value.unwrap())).collect(),
+            ApiResponse::Stock(_) => panic!("Expected an index"),
+        }
+    }
+
+    type Ranges = Vec<(String, String)>;
+
+    // fetches `from`..`to` through the cache on `today`, returns the bars and the ranges requested from the API
+    fn fetch(cache: &MemoryCache, from: &str, to: &str, today: &str) -> (Vec<(String, f64)>, Ranges) {
+        let calls = RefCell::new(Vec::new());
+        let fetch = |from: String, to: String| {
+            calls.borrow_mut().push((from.clone(), to.clone()));
+            let number = calls.borrow().len() as f64;
+            async move { Ok(index_response(&from, &to, number)) }
+        };
+        let response = block_on(fetch_through_cache(cache, "SBITOP", from, to, today, fetch)).unwrap();
+        (bars(&response), calls.into_inner())
+    }
+
+    fn range(from: &str, to: &str) -> Ranges {
+        vec![(from.to_string(), to.to_string())]
+    }
+
+    fn cached(cache: &MemoryCache, from: &str, to: &str, fetched_on: &str) {
+        block_on(cache.put("SBITOP", CachedHistory {
+            from: from.to_string(),
+            to: to.to_string(),
+            fetched_on: fetched_on.to_string(),
+            response: index_response(from, to, 0.0),
+        }));
+    }
+
+    #[test]
+    fn miss_fetches_the_whole_range() {
+        let cache = MemoryCache::default();
+        let (bars, calls) = fetch(&cache, "2024-01-01", "2024-01-10", "2024-01-10");
+        assert_eq!(calls, range("2024-01-01", "2024-01-10"));
+        assert_eq!(bars.len(), 10);
+
+        let entry = block_on(cache.get("SBITOP")).unwrap();
+        assert_eq!((entry.from.as_str(), entry.to.as_str(), entry.fetched_on.as_str()), ("2024-01-01", "2024-01-10", "2024-01-10"));
+    }
+
+    #[test]
+    fn same_day_reuses_the_cached_range() {
+        let cache = MemoryCache::default();
+        cached(&cache, "2024-01-01", "2024-01-10", "2024-01-10");
+        let (bars, calls) = fetch(&cache, "2024-01-03", "2024-01-05", "2024-01-10");
+        assert!(calls.is_empty());
+        assert_eq!(bars, vec![("2024-01-03".to_string(), 0.0), ("2024-01-04".to_string(), 0.0), ("2024-01-05".to_string(), 0.0)]);
+    }
+
+    #[test]
+    fn wider_from_fetches_only_the_head() {
+        let cache = MemoryCache::default();
+        cached(&cache, "2024-01-05", "2024-01-10", "2024-01-10");
+        let (bars, calls) = fetch(&cache, "2024-01-01", "2024-01-10", "2024-01-10");
+        assert_eq!(calls, range("2024-01-01", "2024-01-04"));
+        assert_eq!(bars.len(), 10);
+        assert_eq!(bars[3], ("2024-01-04".to_string(), 1.0));
+        assert_eq!(bars[4], ("2024-01-05".to_string(), 0.0));
+        assert_eq!(block_on(cache.get("SBITOP")).unwrap().from, "2024-01-01");
+    }
+
+    #[test]
+    fn wider_to_refetches_from_the_last_cached_day() {
+        let cache = MemoryCache::default();
+        cached(&cache, "2024-01-01", "2024-01-10", "2024-01-10");
+        let (bars, calls) = fetch(&cache, "2024-01-01", "2024-01-15", "2024-01-15");
+        // the bar of 2024-01-10 could still change on the day it was fetched
+        assert_eq!(calls, range("2024-01-10", "2024-01-15"));
+        assert_eq!(bars.len(), 15);
+        assert_eq!(bars[8], ("2024-01-09".to_string(), 0.0));
+        assert_eq!(bars[9], ("2024-01-10".to_string(), 1.0));
+
+        let entry = block_on(cache.get("SBITOP")).unwrap();
+        assert_eq!((entry.to.as_str(), entry.fetched_on.as_str()), ("2024-01-15", "2024-01-15"));
+    }
+
+    #[test]
+    fn next_day_refetches_the_last_cached_bar() {
+        let cache = MemoryCache::default();
+        cached(&cache, "2024-01-01", "2024-01-10", "2024-01-10");
+        let (bars, calls) = fetch(&cache, "2024-01-01", "2024-01-10", "2024-01-11");
+        assert_eq!(calls, range("2024-01-10", "2024-01-10"));
+        // the refetched bar replaces the cached one
+        assert_eq!(bars.len(), 10);
+        assert_eq!(bars[9], ("2024-01-10".to_string(), 1.0));
+
+        // and is final from then on
+        let (_, calls) = fetch(&cache, "2024-01-01", "2024-01-10", "2024-01-11");
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn invalid_entry_is_fetched_again() {
+        let cache = MemoryCache::default();
+        cached(&cache, "2024-01-01", "2024-01-10", "yesterday");
+        let (bars, calls) = fetch(&cache, "2024-01-01", "2024-01-10", "2024-01-10");
+        assert_eq!(calls, range("2024-01-01", "2024-01-10"));
+        assert_eq!(bars[0], ("2024-01-01".to_string(), 1.0));
+        assert_eq!(block_on(cache.get("SBITOP")).unwrap().fetched_on, "2024-01-10");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn unreadable_cache_file_is_removed() {
+        let dir = std::env::temp_dir().join(format!("evaluator_cache_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cache = DiskCache::new(&dir);
+        std::fs::write(cache.path("SBITOP"), "{\"from\": \"2024-01-01\"").unwrap();
+
+        assert!(block_on(cache.get("SBITOP")).is_none());
+        assert!(!cache.path("SBITOP").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
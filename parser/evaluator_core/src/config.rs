@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::apis::ApiConfig;
+
+/// Evaluator settings passed into `evaluate_script` and the WASM entry point.
+/// Every field is optional when deserialized, missing ones fall back to the defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalConfig {
+    pub api: ApiConfig,
+    // extra FX rates for `currency=`, amount of the currency per 1 EUR
+    pub fx_rates: HashMap<String, f64>,
+    // native only: directory of the on-disk price cache, in-memory cache when not set
+    pub cache_dir: Option<String>,
+}
+
+impl EvalConfig {
+    /// Default config with the API taken from the environment (see `ApiConfig::from_env`)
+    /// and the price cache directory from MONITOR_CACHE_DIR.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_env() -> Self {
+        Self {
+            api: ApiConfig::from_env(),
+            cache_dir: std::env::var("MONITOR_CACHE_DIR").ok(),
+            ..Default::default()
+        }
+    }
+}
@@ -1,9 +1,28 @@
-use crate::{functions::functions::indicator_to_panel_id, helpers::{enum_to_chart_data, get_today, rebase_data, vol_to_chart_data}, response_types::{Chart, ChartType, Derived, ExtraValue, Item, ItemType, MatchingItem, Response, ResponseItem, TrackedItem}};
+use crate::{adjust::adjust_daily_prices, functions::functions::{indicator_chart_type, indicator_panel}, fx::FxRates, helpers::{daily_prices_to_series, enum_to_chart_data, get_today, index_values_to_series, rebase_data, vol_to_chart_data}, resample::{resample_daily_prices, resample_index_values}, response_types::{Chart, ChartType, Derived, ExtraValue, Item, ItemType, MatchingItem, Response, ResponseItem, TrackedItem}};
 use std::collections::{HashMap, HashSet};
-use crate::types::{Stock, Index, DailyPrice, IndexValue};
+use crate::types::{CorporateAction, Stock, Index, DailyPrice, IndexValue, Timeframe};
 use chrono::NaiveDate;
+use futures::{join, stream, StreamExt};
 use crate::apis::*;
-
+use crate::config::EvalConfig;
+use crate::symbols::SymbolDirectory;
+use crate::series_key::SeriesKey;
+use crate::functions::indicator::Point;
+use crate::cache::{fetch_cached, shared_memory_cache, PriceCache};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::cache::DiskCache;
+use std::rc::Rc;
+
+pub const DEFAULT_FROM_DATE: &str = "2015-01-01";
+
+/// Settings the stored series depend on, data of a previous run is only reused when they match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataScope {
+    pub date_range: (String, String),
+    pub timeframe: Timeframe,
+    pub adjusted: bool,
+    pub currency: Option<String>,
+}
 
 #[derive(Debug)]
 pub struct EvalContext {
@@ -14,18 +33,54 @@ pub struct EvalContext {
     // === Time Series ===
     pub price_series: HashMap<String, Vec<DailyPrice>>,
     pub index_series: HashMap<String, Vec<IndexValue>>,
+    pub corporate_actions: HashMap<String, Vec<CorporateAction>>,
 
     // === Derived Series ===
-    pub derived_series: HashMap<String, Vec<(String, (f64, f64, f64, f64))>>,    // (CLOSE, OPEN, HIGH, LOW)
+    pub derived_series: HashMap<SeriesKey, Vec<(String, (f64, f64, f64, f64))>>,    // (CLOSE, OPEN, HIGH, LOW)
 
     // === Metadata / Settings ===
     pub date_range: (String, String),
+    pub timeframe: Timeframe,
+    pub adjusted: bool,
+    pub currency: Option<String>,
+    pub fx_rates: FxRates,
     pub tracked_items: Vec<TrackedItem>,
     pub tracked_ids: HashSet<String>,
     pub rebase: Option<f64>,
     pub extra_data: HashMap<String, HashMap<String, ExtraValue>>,
+    // derived series computed or reused by the current run, only these are charted
+    pub used_series: HashSet<SeriesKey>,
+    // items each cross-sectional function (RANK(ROC(126))) was last computed across
+    pub cross_sections: HashMap<SeriesKey, Vec<String>>,
+
+    // === Session ===
+    // listings of all stocks and indexes, loaded once symbols need resolving
+    pub directory: Option<SymbolDirectory>,
+    pub data_scope: Option<DataScope>,
+    // symbols loaded by `stocks` / `indexes`, in API order
+    pub all_stock_ids: Option<Vec<String>>,
+    pub all_index_ids: Option<Vec<String>>,
+
+    // === API ===
+    pub api: ApiConfig,
+    pub cache: Rc<dyn PriceCache>,
+}
+
+
+async fn fetch_directory(api: &ApiConfig) -> Result<SymbolDirectory, Box<dyn std::error::Error>> {
+    let (stocks, indexes) = join!(fetch_all_stocks(api), fetch_all_indexes(api));
+    Ok(SymbolDirectory::new(stocks?, indexes?))
 }
 
+#[cfg(target_arch = "wasm32")]
+fn default_api_config() -> ApiConfig {
+    ApiConfig::default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_api_config() -> ApiConfig {
+    ApiConfig::from_env()
+}
 
 impl EvalContext {
     pub fn init() -> Self {
@@ -34,12 +89,121 @@ impl EvalContext {
             indexes: HashMap::new(),
             price_series: HashMap::new(),
             index_series: HashMap::new(),
+            corporate_actions: HashMap::new(),
             derived_series: HashMap::new(),
-            date_range: ("2015-01-01".to_string(), get_today()),
+            date_range: (DEFAULT_FROM_DATE.to_string(), get_today()),
+            timeframe: Timeframe::Daily,
+            adjusted: false,
+            currency: None,
+            fx_rates: FxRates::default(),
             tracked_items: Vec::new(),
             tracked_ids: HashSet::new(),
             rebase: None,
             extra_data: HashMap::new(),
+            used_series: HashSet::new(),
+            cross_sections: HashMap::new(),
+            directory: None,
+            data_scope: None,
+            all_stock_ids: None,
+            all_index_ids: None,
+            api: default_api_config(),
+            cache: shared_memory_cache(),
+        }
+    }
+
+    pub fn with_config(config: &EvalConfig) -> Self {
+        let mut ctx = Self::init();
+        ctx.api = config.api.clone();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = &config.cache_dir {
+            ctx.cache = Rc::new(DiskCache::new(dir));
+        }
+        for (currency, per_eur) in &config.fx_rates {
+            ctx.fx_rates.set_rate(currency, *per_eur);
+        }
+        ctx
+    }
+
+    /// Resets settings and results of the previous script, fetched and derived series are kept.
+    pub fn reset_run(&mut self) {
+        self.date_range = (DEFAULT_FROM_DATE.to_string(), get_today());
+        self.timeframe = Timeframe::Daily;
+        self.adjusted = false;
+        self.currency = None;
+        self.tracked_items.clear();
+        self.tracked_ids.clear();
+        self.rebase = None;
+        self.extra_data.clear();
+        self.used_series.clear();
+    }
+
+    /// Stores a series computed by the current run.
+    pub fn store_derived(&mut self, key: SeriesKey, series: Vec<(String, (f64, f64, f64, f64))>) {
+        self.used_series.insert(key.clone());
+        self.derived_series.insert(key, series);
+    }
+
+    // prices of a stock or index
+    fn store_item_series(&mut self, id: &str, series: Vec<(String, (f64, f64, f64, f64))>) {
+        self.derived_series.insert(SeriesKey::item(id), series);
+    }
+
+    /// Key of the series of a tracked item, the expression for derived items.
+    pub fn item_series_key(&self, id: &str) -> SeriesKey {
+        let key = SeriesKey::item(id);
+        if self.derived_series.contains_key(&key) {
+            return key;
+        }
+        self.derived_series
+            .keys()
+            .find(|k| k.item == id && k.function.is_none() && k.expr_hash.is_some())
+            .cloned()
+            .unwrap_or(key)
+    }
+
+    /// Drops stored series when the date range or price options differ from the ones they were built with.
+    /// Corporate actions don't depend on them and are always kept.
+    pub fn retain_data_for_scope(&mut self) {
+        let scope = DataScope {
+            date_range: self.date_range.clone(),
+            timeframe: self.timeframe,
+            adjusted: self.adjusted,
+            currency: self.currency.clone(),
+        };
+        if self.data_scope.as_ref() == Some(&scope) {
+            return;
+        }
+        self.stocks.clear();
+        self.indexes.clear();
+        self.price_series.clear();
+        self.index_series.clear();
+        self.derived_series.clear();
+        self.all_stock_ids = None;
+        self.all_index_ids = None;
+        self.data_scope = Some(scope);
+    }
+
+    /// Loads the listings used to resolve symbols, kept for the rest of the session.
+    /// A failed load leaves it empty and is retried on the next call.
+    pub async fn load_directory(&mut self) {
+        if self.directory.is_some() {
+            return;
+        }
+        match fetch_directory(&self.api).await {
+            Ok(directory) => self.directory = Some(directory),
+            Err(err) => eprintln!("Failed to fetch listings: {}", err),
+        }
+    }
+
+    // tracks every symbol of a list loaded earlier by `stocks` / `indexes`
+    fn track_loaded(&mut self, ids: Vec<String>, item_type: ItemType) {
+        for id in ids {
+            if self.tracked_ids.insert(id.clone()) {
+                self.tracked_items.push(TrackedItem {
+                    id,
+                    item_type: item_type.clone(),
+                });
+            }
         }
     }
 
@@ -51,68 +215,37 @@ impl EvalContext {
         (end - start).num_days().try_into().unwrap()
     }
 
+    /// Value of `last`, the last point of a series computed for the item, if it is dated on the
+    /// item's last bar. An older value is stale and counts as no value.
+    pub async fn value_on_last_bar(&mut self, item_id: &str, last: Option<Point>) -> Option<f64> {
+        let (date, value) = last?;
+        let prices = self.get_item_prices(item_id, false).await?;
+        prices.last().filter(|(last_date, _)| *last_date == date).map(|_| value.0)
+    }
+
     pub async fn get_item_prices(&mut self, item_id: &str, add_to_tracked: bool) -> Option<Vec<(String, (f64, f64, f64, f64))>> {
-        if let Some(ctx_prices) = self.derived_series.get(item_id) {
-            return Some(ctx_prices.clone());
+        if let Some(ctx_prices) = self.derived_series.get(&self.item_series_key(item_id)) {
+            let prices = ctx_prices.clone();
+            if add_to_tracked {
+                self.track_fetched_item(item_id);
+            }
+            return Some(prices);
         }
 
         println!("Fetching prices for item: {}, {}", &self.date_range.0, &self.date_range.1);
 
         // Attempt to fetch
-        match fetch_api_data_async(item_id, &self.date_range.0, &self.date_range.1).await {
+        match fetch_cached(self.cache.as_ref(), &self.api, item_id, &self.date_range.0, &self.date_range.1).await {
             Ok(api_response) => {
-                match api_response {
-                    ApiResponse::Stock(stock_res) => {
-                        self.stocks.insert(item_id.to_string(), stock_res.info);
-                        self.price_series.insert(item_id.to_string(), stock_res.prices.clone());
-                        let prices: Vec<(String, (f64, f64, f64, f64))> = stock_res
-                            .prices
-                            .iter()
-                            .filter_map(|p| Some(
-                                (p.date.clone(), (
-                                    p.last_price.unwrap_or(0.0),
-                                    p.open_price.unwrap_or(p.last_price.unwrap_or(0.0)),
-                                    p.high_price.unwrap_or(0.0),
-                                    p.low_price.unwrap_or(0.0)
-                                ))))
-                            .collect();
-                        self.derived_series.insert(item_id.to_string(), prices.clone());
-                        if add_to_tracked  {    
-                            if self.tracked_ids.insert(item_id.to_string()) {
-                                self.tracked_items.push(TrackedItem {
-                                    id: item_id.to_string(),
-                                    item_type: ItemType::Stock,
-                                });
-                            }
-                        }
-                        Some(prices)
-                    },
-                    ApiResponse::Index(index_res) => {
-                        self.indexes.insert(item_id.to_string(), index_res.info);
-                        self.index_series.insert(item_id.to_string(), index_res.prices.clone());
-                        let prices: Vec<(String, (f64, f64, f64, f64))> = index_res
-                            .prices
-                            .iter()
-                            .filter_map(|p| Some(
-                                (p.date.clone(), (
-                                    p.last_value.unwrap_or(0.0),
-                                    p.open_value.unwrap_or(0.0),
-                                    p.high_value.unwrap_or(0.0),
-                                    p.low_value.unwrap_or(0.0)
-                                ))))
-                            .collect();
-                        self.derived_series.insert(item_id.to_string(), prices.clone());
-                        if add_to_tracked {
-                            if self.tracked_ids.insert(item_id.to_string()) {
-                                self.tracked_items.push(TrackedItem {
-                                    id: item_id.to_string(),
-                                    item_type: ItemType::Index,
-                                });
-                            }
-                        }
-                        Some(prices)
-                    },
+                let actions = match api_response {
+                    ApiResponse::Stock(_) if self.adjusted => Some(self.get_corporate_actions(item_id).await),
+                    _ => None,
+                };
+                let prices = self.store_api_response(item_id, api_response, actions);
+                if add_to_tracked {
+                    self.track_fetched_item(item_id);
                 }
+                Some(prices)
             }
             Err(err) => {
                 eprintln!("Failed to fetch API data for {}: {}", item_id, err);
@@ -121,6 +254,140 @@ impl EvalContext {
         }
     }
 
+    /// Fetches histories of all given symbols concurrently, at most `api.max_concurrency` at a time.
+    /// Items are only stored, tracking them is left to the commands that reference them.
+    pub async fn prefetch_items(&mut self, ids: &[String]) {
+        let mut missing: Vec<String> = Vec::new();
+        for id in ids {
+            if !self.derived_series.contains_key(&SeriesKey::item(id)) && !missing.contains(id) {
+                missing.push(id.clone());
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+
+        let api = &self.api;
+        let cache = self.cache.as_ref();
+        let (from, to) = (&self.date_range.0, &self.date_range.1);
+        let adjusted = self.adjusted;
+        let results: Vec<_> = stream::iter(missing)
+            .map(|id| async move {
+                if adjusted {
+                    let (res, actions) = join!(
+                        fetch_cached(cache, api, &id, from, to),
+                        fetch_corporate_actions(api, &id)
+                    );
+                    (id, res, actions.ok())
+                } else {
+                    let res = fetch_cached(cache, api, &id, from, to).await;
+                    (id, res, None)
+                }
+            })
+            .buffer_unordered(api.max_concurrency.max(1))
+            .collect()
+            .await;
+
+        for (id, res, actions) in results {
+            match res {
+                Ok(api_response) => {
+                    let actions = match api_response {
+                        ApiResponse::Stock(_) if adjusted => {
+                            let actions = actions.unwrap_or_default();
+                            self.corporate_actions.insert(id.clone(), actions.clone());
+                            Some(actions)
+                        }
+                        _ => None,
+                    };
+                    self.store_api_response(&id, api_response, actions);
+                }
+                Err(err) => eprintln!("Failed to fetch API data for {}: {}", id, err),
+            }
+        }
+    }
+
+    fn store_api_response(&mut self, item_id: &str, api_response: ApiResponse, actions: Option<Vec<CorporateAction>>) -> Vec<(String, (f64, f64, f64, f64))> {
+        match api_response {
+            ApiResponse::Stock(stock_res) => {
+                let (info, bars) = self.prepare_stock_history(&stock_res.info, &stock_res.prices, actions.as_deref());
+                self.stocks.insert(item_id.to_string(), info);
+                let prices = daily_prices_to_series(&bars);
+                self.price_series.insert(item_id.to_string(), bars);
+                self.store_item_series(item_id, prices.clone());
+                prices
+            },
+            ApiResponse::Index(index_res) => {
+                self.indexes.insert(item_id.to_string(), index_res.info);
+                let bars = resample_index_values(&index_res.prices, self.timeframe);
+                let prices = index_values_to_series(&bars);
+                self.index_series.insert(item_id.to_string(), bars);
+                self.store_item_series(item_id, prices.clone());
+                prices
+            },
+        }
+    }
+
+    // adds a fetched stock or index to tracked items, derived items are tracked by the evaluator
+    fn track_fetched_item(&mut self, item_id: &str) {
+        let item_type = if self.stocks.contains_key(item_id) {
+            ItemType::Stock
+        } else if self.indexes.contains_key(item_id) {
+            ItemType::Index
+        } else {
+            return;
+        };
+        if self.tracked_ids.insert(item_id.to_string()) {
+            self.tracked_items.push(TrackedItem {
+                id: item_id.to_string(),
+                item_type,
+            });
+        }
+    }
+
+    // currency conversion, corporate action adjustment and resampling of a raw stock history
+    fn prepare_stock_history(&self, info: &Stock, prices: &[DailyPrice], actions: Option<&[CorporateAction]>) -> (Stock, Vec<DailyPrice>) {
+        let mut info = info.clone();
+        let mut daily = prices.to_vec();
+
+        if let Some(currency) = &self.currency {
+            // last price of the stock is quoted in the currency of its latest bar
+            let quoted_in = prices
+                .iter()
+                .max_by(|a, b| a.date.cmp(&b.date))
+                .and_then(|p| p.price_currency.clone())
+                .unwrap_or("EUR".to_string());
+            info = self.fx_rates.convert_stock(&info, &quoted_in, currency);
+            daily = self.fx_rates.convert_daily_prices(&daily, currency);
+        }
+
+        if let Some(actions) = actions {
+            let actions = match &self.currency {
+                Some(currency) => self.fx_rates.convert_actions(actions, currency),
+                None => actions.to_vec(),
+            };
+            daily = adjust_daily_prices(&daily, &actions);
+        }
+
+        (info, resample_daily_prices(&daily, self.timeframe))
+    }
+
+    pub async fn get_corporate_actions(&mut self, item_id: &str) -> Vec<CorporateAction> {
+        if let Some(actions) = self.corporate_actions.get(item_id) {
+            return actions.clone();
+        }
+
+        match fetch_corporate_actions(&self.api, item_id).await {
+            Ok(actions) => {
+                self.corporate_actions.insert(item_id.to_string(), actions.clone());
+                actions
+            }
+            Err(err) => {
+                eprintln!("Failed to fetch corporate actions for {}: {}", item_id, err);
+                Vec::new()
+            }
+        }
+    }
+
     pub fn get_item_data(&mut self, item_id: &str) -> Option<Item> {
         if let Some(stock) = self.stocks.get(item_id) {
             return Some(Item::Stock(stock.clone()));
@@ -136,10 +403,23 @@ impl EvalContext {
     }
 
     pub async fn add_all_indexes_to_tracked(&mut self) {
+        if let Some(ids) = self.all_index_ids.clone() {
+            self.track_loaded(ids, ItemType::Index);
+            return;
+        }
+
         let from = &self.date_range.0;
         let to = &self.date_range.1;
-        let res = fetch_all_indexes().await;
-        let res_prices = fetch_all_indexes_prices(from, to).await;
+        let directory = self.directory.clone();
+        let (res, res_prices) = join!(
+            async {
+                match directory {
+                    Some(directory) => Ok(directory),
+                    None => fetch_directory(&self.api).await,
+                }
+            },
+            fetch_all_indexes_prices(&self.api, from, to)
+        );
 
         let all_prices = match &res_prices {
             Ok(prices) => Some(prices),
@@ -147,34 +427,31 @@ impl EvalContext {
         };
 
         match res {
-            Ok(indexes) => {
-                for s in indexes {
-                    if self.tracked_ids.insert(s.symbol.to_string()) {
+            Ok(directory) => {
+                let ids: Vec<String> = directory.indexes.iter().map(|s| directory.index_id(s)).collect();
+                // only complete loads can be reused by later runs
+                if res_prices.is_ok() {
+                    self.all_index_ids = Some(ids.clone());
+                }
+                for (id, s) in ids.into_iter().zip(&directory.indexes) {
+                    if self.tracked_ids.insert(id.clone()) {
                         self.tracked_items.push(TrackedItem {
-                            id: s.symbol.to_string(),
+                            id: id.clone(),
                             item_type: ItemType::Index,
                         });
                     }
-                    self.indexes.insert(s.symbol.to_string(), s.clone());
+                    self.indexes.insert(id.clone(), s.clone());
 
                     // save prices
                     if let Some(prices_map) = all_prices {
                         if let Some(prices) = prices_map.get(&s.isin) {
-                            self.index_series.insert(s.symbol.to_string(), prices.clone());
-                            let prices: Vec<(String, (f64, f64, f64, f64))> = prices
-                                .iter()
-                                .filter_map(|p| Some(
-                                    (p.date.clone(), (
-                                        p.last_value.unwrap_or(0.0),
-                                        p.open_value.unwrap_or(0.0),
-                                        p.high_value.unwrap_or(0.0),
-                                        p.low_value.unwrap_or(0.0)
-                                    ))))
-                                    .collect();
-                            self.derived_series.insert(s.symbol.to_string(), prices.clone());
+                            let bars = resample_index_values(prices, self.timeframe);
+                            self.store_item_series(&id, index_values_to_series(&bars));
+                            self.index_series.insert(id, bars);
                         }
                     }
                 }
+                self.directory = Some(directory);
             },
             Err(err) => {
                 eprintln!("Failed to fetch all indexes: {}", err);
@@ -183,10 +460,31 @@ impl EvalContext {
     }
 
     pub async fn add_all_stocks_to_tracked(&mut self) {
+        if let Some(ids) = self.all_stock_ids.clone() {
+            self.track_loaded(ids, ItemType::Stock);
+            return;
+        }
+
         let from = &self.date_range.0;
         let to = &self.date_range.1;
-        let res = fetch_all_stocks().await;
-        let res_prices = fetch_all_stocks_prices(from, to).await;
+        let adjusted = self.adjusted;
+        let directory = self.directory.clone();
+        let (res, res_prices, res_actions) = join!(
+            async {
+                match directory {
+                    Some(directory) => Ok(directory),
+                    None => fetch_directory(&self.api).await,
+                }
+            },
+            fetch_all_stocks_prices(&self.api, from, to),
+            async {
+                if adjusted {
+                    fetch_all_corporate_actions(&self.api).await.ok()
+                } else {
+                    None
+                }
+            }
+        );
 
         let all_prices = match &res_prices {
             Ok(prices) => Some(prices),
@@ -194,34 +492,38 @@ impl EvalContext {
         };
 
         match res {
-            Ok(stocks) => {
-                for s in stocks {
-                    if self.tracked_ids.insert(s.symbol.to_string()) {
+            Ok(directory) => {
+                let ids: Vec<String> = directory.stocks.iter().map(|s| directory.stock_id(s)).collect();
+                // only complete loads can be reused by later runs
+                if res_prices.is_ok() && (!adjusted || res_actions.is_some()) {
+                    self.all_stock_ids = Some(ids.clone());
+                }
+                for (id, s) in ids.into_iter().zip(&directory.stocks) {
+                    if self.tracked_ids.insert(id.clone()) {
                         self.tracked_items.push(TrackedItem {
-                            id: s.symbol.to_string(),
+                            id: id.clone(),
                             item_type: ItemType::Stock,
                         });
                     }
-                    self.stocks.insert(s.symbol.to_string(), s.clone());
+                    self.stocks.insert(id.clone(), s.clone());
 
                     // save price
                     if let Some(prices_map) = all_prices {
                         if let Some(prices) = prices_map.get(&s.isin) {
-                            self.price_series.insert(s.symbol.to_string(), prices.clone());
-                            let prices: Vec<(String, (f64, f64, f64, f64))> = prices
-                                .iter()
-                                .filter_map(|p| Some(
-                                    (p.date.clone(), (
-                                        p.last_price.unwrap_or(0.0),
-                                        p.open_price.unwrap_or(p.last_price.unwrap_or(0.0)),
-                                        p.high_price.unwrap_or(0.0),
-                                        p.low_price.unwrap_or(0.0)
-                                    ))))
-                                .collect();
-                            self.derived_series.insert(s.symbol.to_string(), prices.clone());
+                            let actions = res_actions
+                                .as_ref()
+                                .map(|actions_map| actions_map.get(&s.isin).cloned().unwrap_or_default());
+                            if let Some(actions) = &actions {
+                                self.corporate_actions.insert(id.clone(), actions.clone());
+                            }
+                            let (info, bars) = self.prepare_stock_history(s, prices, actions.as_deref());
+                            self.stocks.insert(id.clone(), info);
+                            self.store_item_series(&id, daily_prices_to_series(&bars));
+                            self.price_series.insert(id, bars);
                         }
                     }
                 }
+                self.directory = Some(directory);
             },
             Err(err) => {
                 eprintln!("Failed to fetch all stocks: {}", err);
@@ -239,13 +541,14 @@ impl EvalContext {
         };
 
         let tracked_items = self.tracked_items.clone();
-        for tracked_item in tracked_items {
+        for tracked_item in &tracked_items {
             // add to matching items
             let data = self.get_item_data(&tracked_item.id);
             let extra_data = self.extra_data.get(&tracked_item.id).cloned().unwrap_or_default();
             match data {
                 Some(d) => {
                     let obj: MatchingItem = MatchingItem {
+                        key: self.item_series_key(&tracked_item.id),
                         item: match d {
                             Item::Index(index) => ResponseItem::Index(index),
                             Item::Stock(stock) => ResponseItem::Stock(stock),
@@ -265,7 +568,8 @@ impl EvalContext {
                         .as_mut()
                         .expect("Expected matching_items to be Some")
                         .push(MatchingItem {
-                            item: ResponseItem::Derived(Derived {id: tracked_item.id}),
+                            key: self.item_series_key(&tracked_item.id),
+                            item: ResponseItem::Derived(Derived {id: tracked_item.id.clone()}),
                             extra_data: extra_data
                         });
                 }
@@ -273,34 +577,46 @@ impl EvalContext {
         }
 
         if has_plot {
+            // indicators without a shared range get a panel each (MACD_12,26,9 -> 2, ADX_14 -> 3)
+            let mut separate_panels: Vec<String> = Vec::new();
             // add charts
             if let Some(items) = &response.matching_items {  
-                for item in items {
+                // matching items are in the order of tracked items
+                for tracked_item in tracked_items.iter().take(items.len()) {
                     // create chart {} for all ids in derived series hashmap that include item id and push to charts
-                    let id = match &item.item {
-                        ResponseItem::Derived(d) => &d.id,
-                        ResponseItem::Index(i) => &i.symbol,
-                        ResponseItem::Stock(s) => &s.symbol
-                    };
+                    let id = &tracked_item.id;
 
                     let matches = self.get_matching_values_from_derived(id);
 
-                    // add all prices that are in derived
-                    for vec in matches {
-                        let chart_id = vec.0;
-                        let mut chart_type = ChartType::Price;
-                        let mut panel_id = 0;
-                        if chart_id.contains("_") {
-                            chart_type = ChartType::Indicator;
-                            panel_id = indicator_to_panel_id(&chart_id);
-                        }
-                        let mut chart_data = enum_to_chart_data(vec.1.clone());
-                        if let Some(rebase) = rebase {
+                    // add the prices of the item and all series computed from it
+                    for (key, series) in matches {
+                        let (mut chart_type, panel_id) = match &key.function {
+                            Some(name) => {
+                                let panel = indicator_panel(&key);
+                                let panel_id = match panel.panel_id() {
+                                    2 => {
+                                        let panel = format!("{}_{}_{:?}", name, key.params.join(","), panel);
+                                        let idx = separate_panels.iter().position(|p| *p == panel).unwrap_or_else(|| {
+                                            separate_panels.push(panel);
+                                            separate_panels.len() - 1
+                                        });
+                                        2 + idx as i32
+                                    }
+                                    panel_id => panel_id,
+                                };
+                                (indicator_chart_type(&key), panel_id)
+                            }
+                            None => (ChartType::Price, 0),
+                        };
+                        let mut chart_data = enum_to_chart_data(series);
+                        // markers stay on the candles, only values are rebased
+                        if let Some(rebase) = rebase.filter(|_| !matches!(chart_type, ChartType::Marker)) {
                             chart_data = rebase_data(&chart_data, rebase);
                             chart_type = ChartType::Rebase;
                         }
                         let chart = Chart {
-                            id: chart_id.to_string(),
+                            id: key.id(),
+                            key,
                             chart_type: chart_type,
                             panel_id: panel_id,
                             data: chart_data
@@ -310,12 +626,13 @@ impl EvalContext {
                             .push(chart);
                     }
 
-                    // add volume - if stock - 4 letter id
-                    if id.chars().count() == 4 && rebase == None{
+                    // add volume of stocks, indexes and derived items have none
+                    if matches!(tracked_item.item_type, ItemType::Stock) && rebase.is_none() {
                         let volume_data = self.get_volume_for_stock(id);
                         let vol_data = vol_to_chart_data(volume_data);
                         let volume_chart = Chart {
                             id: id.to_string() + "_volume",
+                            key: SeriesKey::function("VOLUME", Vec::new(), id),
                             chart_type: ChartType::Volume,
                             panel_id: 0,
                             data: vol_data
@@ -335,13 +652,14 @@ impl EvalContext {
         response
     }
 
+    /// Series drawn with item `x`: its own prices and everything the current run computed from it.
     pub fn get_matching_values_from_derived(
         &self,
         x: &str,
-    ) -> Vec<(String, Vec<(String, (f64, f64, f64, f64))>)> {
+    ) -> Vec<(SeriesKey, Vec<(String, (f64, f64, f64, f64))>)> {
         self.derived_series
             .iter()
-            .filter(|(key, _)| key.contains(x))
+            .filter(|(key, _)| key.item == x && (key.is_item() || self.used_series.contains(*key)))
             .map(|(key, vec)| (key.clone(), vec.clone()))
             .collect()
     }
@@ -1,8 +1,11 @@
 use std::pin::Pin;
 use parser_core::ast::{Comparator, LogicalExpr, LogicalOp, NamedArg, Operand, Value};
-use crate::{context::EvalContext, evaluator::evaluate_function_call, response_types::{ExtraValue, Item, TrackedItem}};
+use crate::{context::EvalContext, evaluator::{compute_expr_series, evaluate_function_call}, helpers::{bar_field_series, is_bar_field}, response_types::{ExtraValue, Item, TrackedItem}};
 
 
+// an operand without a value, every comparison with NaN is false
+const NO_VALUE: f64 = f64::NAN;
+
 pub async fn filter_eval(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
     let mut conditions: Option<LogicalExpr> = None;
 
@@ -20,15 +23,18 @@ pub async fn filter_eval(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
 
     if let Some(expr) = conditions {
         // evaluate logical expression
-        // filter tracked items based on the expression
+        // filter tracked items based on the expression, all items are evaluated before any is removed
+        // so cross-sectional functions (PCTRANK(ROC(126))) compare the same items for each of them
         let tracked_items = ctx.tracked_items.clone();
+        let mut rejected = Vec::new();
         for tracked_item in tracked_items {
             let eval = evaluate_condition(ctx, &expr, &tracked_item).await;
             if !eval {
-                // remove item from tracked if it does not match the condition
-                ctx.tracked_items.retain(|item| item.id != tracked_item.id);
+                rejected.push(tracked_item.id);
             }
         }
+        // remove items from tracked if they do not match the condition
+        ctx.tracked_items.retain(|item| !rejected.contains(&item.id));
     } else {
         panic!("No filter conditions provided");
     }
@@ -70,92 +76,37 @@ async fn evaluate_operand(ctx: &mut EvalContext, operand: &Operand, item: &Track
     match operand {
         Operand::Number(num) => *num,
         
-        Operand::Ident(ident) => {
-            let item_data = ctx.get_item_data(&item.id);
-            match item_data {
-                Some(data) => match ident.as_str() {
-                    "price" => {
-                        match data {
-                            Item::Stock(stock) => stock.last_price.unwrap_or(0.0),
-                            Item::Index(index) => index.last_value.unwrap_or(0.0),
-                        }
-                    },
-                    "change" => {
-                        match data {
-                            Item::Stock(stock) => stock.change_prev_close_percentage.unwrap_or(0.0),
-                            Item::Index(index) => index.change_prev_close_percentage.unwrap_or(0.0),
-                        }
-                    },
-                    "country" => {
-                        match data {
-                            Item::Stock(stock) => {
-                                if stock.mic == "XZAG" { 
-                                    ctx.save_extra_data(&stock.symbol, "country", ExtraValue::Text("Croatia".to_string()));
-                                    0.0
-                                } else {
-                                    ctx.save_extra_data(&stock.symbol, "country", ExtraValue::Text("Slovenia".to_string()));
-                                    1.0
-                                }
-                            }
-                            Item::Index(index) => {
-                                if index.mic == "XZAG" {
-                                    ctx.save_extra_data(&index.symbol, "country", ExtraValue::Text("Croatia".to_string()));
-                                    0.0
-                                } else {
-                                    ctx.save_extra_data(&index.symbol, "country", ExtraValue::Text("Slovenia".to_string()));
-                                    1.0
-                                }
-                            }
-                        }
-                    },
-                    "market_cap" => {
-                        match data {
-                            Item::Stock(stock) => {
-                                let mc = stock.quantity.unwrap_or(0) as f64 * stock.last_price.unwrap_or(0.0);
-                                ctx.save_extra_data(&stock.symbol, "market_cap", ExtraValue::Number(mc));
-                                mc
-                            },
-                            Item::Index(_) => 0.0,
-                        }
-                    }
-                    // other side identifiers
-                    "si" => 1.0,
-                    "hr" => 0.0,
-                    "at" => 2.0,
-                    _ => {
-                        if let Ok(num) = ident.parse::<f64>() {
-                            num
-                        } else {
-                            panic!("Unknown identifier in filter condition: {}", ident);
-                        }
-                    },
-                },
-                None => panic!("No data found for item {}", item.id),
+        // last close, open, high or low of the item
+        Operand::Ident(field) if is_bar_field(field) => {
+            match ctx.get_item_prices(&item.id, false).await {
+                Some(series) => bar_field_series(&series, field).last().map_or(0.0, |(_, v)| v.0),
+                None => panic!("No series found for item {}", item.id),
             }
         },
+
+        Operand::Ident(ident) => item_field_value(ctx, &item.id, ident),
         
+        // value of the item's last bar, an older one is stale and no value fails the condition
         Operand::FunctionCall(func_call) => {
-            let res_id = evaluate_function_call(ctx, func_call).await;
-            let id = res_id + &item.id;
-            if let Some(series) = ctx.derived_series.get(&id) {
-                let func_id_clear = match id.rfind('_') {
-                    Some(pos) => &id[..pos],
-                    None => &id,
-                };
-
-                if series.is_empty() {
-                    ctx.save_extra_data(&item.id, func_id_clear, ExtraValue::Number(0.0));
-                    0.0
-                } else {
-                    let res = series[series.len() - 1].1.0;    // take price (.1) and close value - only important one (.0)
-                    ctx.save_extra_data(&item.id, func_id_clear, ExtraValue::Number(res));
-                    res
+            let key = evaluate_function_call(ctx, func_call).await.for_item(&item.id);
+            let last = match ctx.derived_series.get(&key) {
+                Some(series) => series.last().cloned(),
+                None => panic!("No series found for function call {}", key),
+            };
+            match ctx.value_on_last_bar(&item.id, last).await {
+                Some(value) => {
+                    ctx.save_extra_data(&item.id, &key.label(), ExtraValue::Number(value));
+                    value
                 }
-            } else {
-                panic!("No series found for function call {}", id);
+                None => NO_VALUE,
             }
         },
 
+        Operand::ArithmeticExpr(expr) => {
+            let series = compute_expr_series(ctx, expr, Some(item)).await;
+            ctx.value_on_last_bar(&item.id, series.last().cloned()).await.unwrap_or(NO_VALUE)
+        },
+
         Operand::LogicalExpr(expr) => {
             let res = evaluate_condition(ctx, expr, item).await;
             if res {
@@ -165,4 +116,147 @@ async fn evaluate_operand(ctx: &mut EvalContext, operand: &Operand, item: &Track
             }
         },
     }
-}
\ No newline at end of file
+}
+
+/// Value of an item data field (price, change, market_cap, country, ...) from the item listing.
+pub fn item_field_value(ctx: &mut EvalContext, item_id: &str, ident: &str) -> f64 {
+    let item_data = ctx.get_item_data(item_id);
+    match item_data {
+        Some(data) => match ident {
+            "price" => {
+                match data {
+                    Item::Stock(stock) => stock.last_price.unwrap_or(0.0),
+                    Item::Index(index) => index.last_value.unwrap_or(0.0),
+                }
+            },
+            "change" => {
+                match data {
+                    Item::Stock(stock) => stock.change_prev_close_percentage.unwrap_or(0.0),
+                    Item::Index(index) => index.change_prev_close_percentage.unwrap_or(0.0),
+                }
+            },
+            "country" => {
+                match data {
+                    Item::Stock(stock) => {
+                        if stock.mic == "XZAG" { 
+                            ctx.save_extra_data(item_id, "country", ExtraValue::Text("Croatia".to_string()));
+                            0.0
+                        } else {
+                            ctx.save_extra_data(item_id, "country", ExtraValue::Text("Slovenia".to_string()));
+                            1.0
+                        }
+                    }
+                    Item::Index(index) => {
+                        if index.mic == "XZAG" {
+                            ctx.save_extra_data(item_id, "country", ExtraValue::Text("Croatia".to_string()));
+                            0.0
+                        } else {
+                            ctx.save_extra_data(item_id, "country", ExtraValue::Text("Slovenia".to_string()));
+                            1.0
+                        }
+                    }
+                }
+            },
+            "market_cap" => {
+                match data {
+                    Item::Stock(stock) => {
+                        let mc = stock.quantity.unwrap_or(0) as f64 * stock.last_price.unwrap_or(0.0);
+                        ctx.save_extra_data(item_id, "market_cap", ExtraValue::Number(mc));
+                        mc
+                    },
+                    Item::Index(_) => 0.0,
+                }
+            }
+            // other side identifiers
+            "si" => 1.0,
+            "hr" => 0.0,
+            "at" => 2.0,
+            _ => {
+                if let Ok(num) = ident.parse::<f64>() {
+                    num
+                } else {
+                    panic!("Unknown identifier in filter condition: {}", ident);
+                }
+            },
+        },
+        None => panic!("No data found for item {}", item_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use parser_core::ast::{ArithmeticOp, Expr, FunctionArg, FunctionCall};
+    use crate::response_types::ItemType;
+    use crate::series_key::SeriesKey;
+
+    // context tracking one derived item of the given closes, one bar per day from 2024-01-01
+    fn context(closes: &[f64]) -> (EvalContext, TrackedItem) {
+        let mut ctx = EvalContext::init();
+        let prices = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| (format!("2024-01-{:02}", i + 1), (*close, *close, *close, *close)))
+            .collect();
+        ctx.derived_series.insert(SeriesKey::item("A"), prices);
+        let item = TrackedItem { id: "A".to_string(), item_type: ItemType::Derived };
+        ctx.tracked_ids.insert(item.id.clone());
+        ctx.tracked_items.push(item.clone());
+        (ctx, item)
+    }
+
+    // DIST_TO_RESISTANCE(2) compared with a number
+    fn dist_to_resistance(op: Comparator, value: f64) -> LogicalExpr {
+        LogicalExpr::Comparison {
+            left: Operand::FunctionCall(FunctionCall {
+                name: "DIST_TO_RESISTANCE".to_string(),
+                args: vec![FunctionArg::Number(2.0)],
+                output: None,
+            }),
+            op,
+            right: Operand::Number(value),
+        }
+    }
+
+    #[test]
+    fn function_value_of_the_last_bar() {
+        let (mut ctx, item) = context(&[10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 11.0, 11.5]);
+        assert!(block_on(evaluate_condition(&mut ctx, &dist_to_resistance(Comparator::Lt, 50.0), &item)));
+    }
+
+    #[test]
+    fn stale_function_value_fails() {
+        // the last two closes are above the only swing high, the last distance is from 2024-01-07
+        let (mut ctx, item) = context(&[10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 13.0, 14.0]);
+        assert!(!block_on(evaluate_condition(&mut ctx, &dist_to_resistance(Comparator::Lt, 50.0), &item)));
+    }
+
+    #[test]
+    fn stale_expression_value_fails() {
+        let (mut ctx, item) = context(&[10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 13.0, 14.0]);
+        let dist = match dist_to_resistance(Comparator::Lt, 50.0) {
+            LogicalExpr::Comparison { left: Operand::FunctionCall(call), .. } => call,
+            _ => unreachable!(),
+        };
+        // DIST_TO_RESISTANCE(2) * 1 < 50
+        let condition = LogicalExpr::Comparison {
+            left: Operand::ArithmeticExpr(Expr::BinaryOp {
+                left: Box::new(Expr::FunctionCall(dist)),
+                op: ArithmeticOp::Mul,
+                right: Box::new(Expr::Number(1.0)),
+            }),
+            op: Comparator::Lt,
+            right: Operand::Number(50.0),
+        };
+        assert!(!block_on(evaluate_condition(&mut ctx, &condition, &item)));
+    }
+
+    #[test]
+    fn missing_function_value_fails() {
+        let (mut ctx, item) = context(&[10.0, 11.0, 12.0]);
+        assert!(!block_on(evaluate_condition(&mut ctx, &dist_to_resistance(Comparator::Lt, 50.0), &item)));
+        // not even a comparison any number passes
+        assert!(!block_on(evaluate_condition(&mut ctx, &dist_to_resistance(Comparator::Neq, 1.0), &item)));
+    }
+}
@@ -1,16 +1,16 @@
 use std::collections::HashMap;
 use parser_core::ast::{NamedArg, Value};
-use crate::{context::EvalContext, evaluator::{compute_expr_series, evaluate_function_call}, helpers::expr_to_id, response_types::{Item, TrackedItem, ExtraValue}, types::Direction};
+use crate::{context::EvalContext, evaluator::{compute_expr_series, evaluate_function_call}, helpers::expr_to_id, series_key::SeriesKey, response_types::{Item, TrackedItem, ExtraValue}, types::Direction};
 
 pub async  fn sort_eval(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
     let mut direction: Direction = Direction::Asc;
     let mut limit: Option<u64> = None;
     let mut field: Option<String> = None;
-    let mut expr_id: Option<Vec<String>> = None;
-    let mut func_id: Option<String> = None;
+    let mut expr_id: Option<Vec<SeriesKey>> = None;
+    let mut func_id: Option<SeriesKey> = None;
 
-    // id to derived_id (KRKG -> RSI_14_KRKG+RSI_10_KRKG)
-    let mut id_hash_map: HashMap<String, String> = HashMap::new(); 
+    // id to derived key (KRKG -> RSI_14_KRKG+RSI_10_KRKG)
+    let mut id_hash_map: HashMap<String, SeriesKey> = HashMap::new(); 
 
     for arg in args {
         match arg.name.as_str() {
@@ -32,18 +32,19 @@ pub async  fn sort_eval(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
                         }
                     }
                     Value::FunctionCall(func_call) => {
-                        let id = evaluate_function_call(ctx, func_call).await;
-                        func_id = Some(id);
+                        let key = evaluate_function_call(ctx, func_call).await;
+                        func_id = Some(key);
                     }
                     Value::ArithmeticExpr(expr) => {
                         // for each tracked item do this
                         let existing_items: Vec<TrackedItem> = ctx.tracked_items.clone(); 
                         for tracked_item in existing_items {
-                            let id = expr_to_id(expr, Some(&tracked_item));
+                            // sort key of the item, charted only if the expression itself is tracked
+                            let key = SeriesKey::expr(expr, &expr_to_id(expr, Some(&tracked_item)));
                             let series = compute_expr_series(ctx, expr, Some(&tracked_item)).await;
-                            ctx.derived_series.insert(id.clone(), series);
-                            expr_id.get_or_insert_with(Vec::new).push(id.clone());
-                            id_hash_map.insert(tracked_item.id.clone(), id);
+                            ctx.store_derived(key.clone(), series);
+                            expr_id.get_or_insert_with(Vec::new).push(key.clone());
+                            id_hash_map.insert(tracked_item.id.clone(), key);
                         }
                     }
                     _ => panic!("Expected an Ident for 'item', got {:?}", arg.value),
@@ -76,73 +77,36 @@ pub async  fn sort_eval(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
     // if exprId is Some check derived_series (for each id in tracked items)
     // if funcId is Some check derived_series (for each id in tracked items - append to funcId)
     // rearrange tracked_items based on sorting criteria (and cut if limit)
-    let to = ctx.date_range.1.clone();
     let mut sorted_items = ctx.tracked_items.clone();
-    if field.is_none() && expr_id.is_none() && func_id.is_some() {
-        // FUNCTION
-        // loop through tracked items, get derived series for each id (func_id + id (RSI_14_ + id)) and compare
-        sorted_items.sort_by(|a, b| {
-            let a_id = format!("{}{}", func_id.as_deref().unwrap_or(""), a.id);
-            let b_id = format!("{}{}", func_id.as_deref().unwrap_or(""), b.id);
-
-            let a_val = ctx.derived_series.get(&a_id)
-                .and_then(|series| series.last())
-                .cloned()
-                .unwrap_or((to.clone(), (f64::MIN, f64::MIN, f64::MIN, f64::MIN)));
-
-            let b_val = ctx.derived_series.get(&b_id)
-                .and_then(|series| series.last())
-                .cloned()
-                .unwrap_or((to.clone(), (f64::MIN, f64::MIN, f64::MIN, f64::MIN)));
-
-            let ordering = a_val
-                .partial_cmp(&b_val)
-                .unwrap_or(std::cmp::Ordering::Equal);
-
-            let a_data_id = match a_id.rfind('_') {
-                Some(pos) => &a_id[..pos],
-                None => &a_id,
-            };
-            let b_data_id = match b_id.rfind('_') {
-                Some(pos) => &b_id[..pos],
-                None => &b_id,
+    if field.is_none() && expr_id.is_some() != func_id.is_some() {
+        // FUNCTION or EXPRESSION
+        // value of each item on its last bar, items without one (or with a stale one) go last
+        let mut values: HashMap<String, Option<f64>> = HashMap::new();
+        for item in &sorted_items {
+            let key = match &func_id {
+                Some(func_key) => Some(func_key.for_item(&item.id)),
+                None => id_hash_map.get(&item.id).cloned(),
             };
-
-            // save to extra_data
-            ctx.save_extra_data(a.id.as_str(), a_data_id, ExtraValue::Number(a_val.1.0));
-            ctx.save_extra_data(&b.id, b_data_id, ExtraValue::Number(b_val.1.0));
-
-            match direction {
-                Direction::Asc => ordering,
-                Direction::Desc => ordering.reverse(),
+            let last = key.as_ref().and_then(|key| ctx.derived_series.get(key)).and_then(|series| series.last().cloned());
+            let value = ctx.value_on_last_bar(&item.id, last).await;
+            // function values are shown with the items
+            if let (Some(func_key), Some(value)) = (&func_id, value) {
+                ctx.save_extra_data(&item.id, &func_key.for_item(&item.id).label(), ExtraValue::Number(value));
             }
-        });
-    } else if field.is_none() && expr_id.is_some() && func_id.is_none() {
-        // EXPRESSION
-        // loop through tracked items, get derived id from each id and get derived series for each derived id and compare
-        sorted_items.sort_by(|a, b| {
-            let a_val = id_hash_map
-                .get(&a.id)
-                .and_then(|d_id| ctx.derived_series.get(d_id))
-                .and_then(|series| series.last())
-                .cloned()
-                .unwrap_or((to.clone(), (f64::MIN, f64::MIN, f64::MIN, f64::MIN)));
-
-            let b_val = id_hash_map
-                .get(&b.id)
-                .and_then(|d_id| ctx.derived_series.get(d_id))
-                .and_then(|series| series.last())
-                .cloned()
-                .unwrap_or((to.clone(), (f64::MIN, f64::MIN, f64::MIN, f64::MIN)));
-
-            let ordering = a_val
-                .partial_cmp(&b_val)
-                .unwrap_or(std::cmp::Ordering::Equal);
+            values.insert(item.id.clone(), value);
+        }
 
-            match direction {
-                Direction::Asc => ordering,
-                Direction::Desc => ordering.reverse(),
+        sorted_items.sort_by(|a, b| match (values[&a.id], values[&b.id]) {
+            (Some(a_val), Some(b_val)) => {
+                let ordering = a_val.partial_cmp(&b_val).unwrap_or(std::cmp::Ordering::Equal);
+                match direction {
+                    Direction::Asc => ordering,
+                    Direction::Desc => ordering.reverse(),
+                }
             }
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
         });
     } else if field.is_some() && expr_id.is_none() && func_id.is_none() {
         // FIELD
@@ -328,4 +292,58 @@ pub async  fn sort_eval(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
     }
 
     ctx.tracked_items = sorted_items;
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use parser_core::ast::{FunctionArg, FunctionCall};
+    use crate::response_types::ItemType;
+
+    // context tracking derived items of the given closes, one bar per day from 2024-01-01
+    fn context(items: &[(&str, &[f64])]) -> EvalContext {
+        let mut ctx = EvalContext::init();
+        for (id, closes) in items {
+            let prices = closes
+                .iter()
+                .enumerate()
+                .map(|(i, close)| (format!("2024-01-{:02}", i + 1), (*close, *close, *close, *close)))
+                .collect();
+            ctx.derived_series.insert(SeriesKey::item(id), prices);
+            ctx.tracked_ids.insert(id.to_string());
+            ctx.tracked_items.push(TrackedItem { id: id.to_string(), item_type: ItemType::Derived });
+        }
+        ctx
+    }
+
+    // SORT(property=DIST_TO_RESISTANCE(2), dir=...)
+    fn sort_by_distance(ctx: &mut EvalContext, dir: &str) -> Vec<String> {
+        let args = vec![
+            NamedArg {
+                name: "property".to_string(),
+                value: Value::FunctionCall(FunctionCall {
+                    name: "DIST_TO_RESISTANCE".to_string(),
+                    args: vec![FunctionArg::Number(2.0)],
+                    output: None,
+                }),
+            },
+            NamedArg { name: "dir".to_string(), value: Value::Ident(dir.to_string()) },
+        ];
+        block_on(sort_eval(ctx, &args));
+        ctx.tracked_items.iter().map(|item| item.id.clone()).collect()
+    }
+
+    #[test]
+    fn items_with_a_stale_value_sort_last() {
+        let items: &[(&str, &[f64])] = &[
+            // above its only swing high for the last two bars, the last distance is from 2024-01-07
+            ("STALE", &[10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 13.0, 14.0]),
+            ("FAR", &[10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 11.0, 10.0]),
+            ("NEAR", &[10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 11.0, 11.5]),
+        ];
+        let mut ctx = context(items);
+        assert_eq!(sort_by_distance(&mut ctx, "asc"), vec!["NEAR", "FAR", "STALE"]);
+        let mut ctx = context(items);
+        assert_eq!(sort_by_distance(&mut ctx, "desc"), vec!["FAR", "NEAR", "STALE"]);
+    }
+}
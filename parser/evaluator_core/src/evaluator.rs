@@ -1,41 +1,79 @@
 use parser_core::ast::*;
-use crate::eval_filter::filter_eval;
+use crate::eval_filter::{filter_eval, item_field_value};
 use crate::eval_plot::plot_eval;
 use crate::response_types::{ItemType, Response, TrackedItem};
-use crate::helpers::{create_function_id, expr_to_id, get_today, number_series_with_dates};
+use crate::helpers::{bar_field_series, expr_to_id, get_today, is_bar_field, is_item_field, number_series_with_dates};
+use crate::series_key::SeriesKey;
 use crate::context::*;
-use std::collections::HashMap;
+use crate::config::EvalConfig;
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
 use std::future::Future;
-use crate::functions::functions::handle_calculate_function;
+use crate::functions::cross_section::CrossSection;
+use crate::functions::indicator::is_choice_arg;
+use crate::functions::registry::registry;
+use crate::functions::functions::{call_item, function_key, handle_calculate_function, is_bar_transform};
 use crate::eval_sort::sort_eval;
+use crate::types::Timeframe;
+use crate::symbols::SymbolDirectory;
 
 
-pub async fn evaluate_input(program: &Program) -> Response {
+pub async fn evaluate_input(program: &Program, config: &EvalConfig) -> Response {
+    let mut context = EvalContext::with_config(config);
+    evaluate_program(&mut context, program).await
+}
+
+/// Evaluates a program in an existing context. Series fetched or computed by earlier
+/// programs are reused as long as the date range and price options stay the same.
+pub async fn evaluate_program(context: &mut EvalContext, program: &Program) -> Response {
     let mut is_first = true;
-    let mut context = EvalContext::init();
+    context.reset_run();
     let mut has_plot = false;
     let mut has_backtest = false;
 
-    // set date range first
+    // set date range and price options first
+    let mut options_set = HashSet::new();
     for command in &program.commands {
         match command {
             Command::Plot(args) => {
-                evaluate_date_range(&mut context, args);
+                evaluate_date_range(context, args);
+                evaluate_price_options(context, args, &mut options_set);
                 has_plot = true;
             },
-            Command::Backtest(_) => has_backtest = true,
+            Command::Filter(args) | Command::Sort(args) => evaluate_price_options(context, args, &mut options_set),
+            Command::Backtest(args) => {
+                evaluate_price_options(context, args, &mut options_set);
+                has_backtest = true;
+            },
             _ => {}
         }
     }
 
+    // the first command decides the final date range (see evaluate_first), known symbols are fetched in one batch
+    if let Some(first) = program.commands.first() {
+        evaluate_date_range(context, command_args(first));
+    }
+    context.retain_data_for_scope();
+
+    // symbols are resolved against the listings only when the script names any
+    let mut symbols = collect_symbols(program);
+    let resolved;
+    let program = if symbols.is_empty() {
+        program
+    } else {
+        context.load_directory().await;
+        (resolved, symbols) = resolve_symbols(program, context.directory.as_ref().unwrap_or(&SymbolDirectory::default()));
+        &resolved
+    };
+    context.prefetch_items(&symbols).await;
+
     for command in &program.commands {
         match command {
-            Command::Filter(args) => evaluate_filter(&mut context, args, is_first).await,
-            Command::Sort(args) => evaluate_sort(&mut context, args, is_first).await,
-            Command::Backtest(args) => evaluate_backtest(&mut context, args, is_first).await,
-            Command::Plot(args) => evaluate_plot(&mut context, args, is_first).await,
-            Command::Group(args) => evaluate_group(&mut context, args, is_first).await,
+            Command::Filter(args) => evaluate_filter(context, args, is_first).await,
+            Command::Sort(args) => evaluate_sort(context, args, is_first).await,
+            Command::Backtest(args) => evaluate_backtest(context, args, is_first).await,
+            Command::Plot(args) => evaluate_plot(context, args, is_first).await,
+            Command::Group(args) => evaluate_group(context, args, is_first).await,
         }
         is_first = false;
     }
@@ -108,6 +146,49 @@ fn evaluate_date_range(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
     }
 }
 
+// options that change how raw prices are turned into series, must be known before any fetch.
+// They apply to every command of the script, `options_set` holds the ones an earlier command set
+fn evaluate_price_options(ctx: &mut EvalContext, args: &Vec<NamedArg>, options_set: &mut HashSet<&'static str>) {
+    for arg in args {
+        match arg.name.as_str() {
+            "timeframe" => {
+                match &arg.value {
+                    Value::Duration(duration) => {
+                        let timeframe = Timeframe::from_duration(duration)
+                            .unwrap_or_else(|| panic!("Unsupported timeframe: {}, expected 1d, 1w, 1m or 1q", duration));
+                        set_price_option(&mut ctx.timeframe, timeframe, "timeframe", options_set);
+                    }
+                    _ => panic!("Expected a Duration for 'timeframe', got {:?}", arg.value),
+                }
+            }
+            "adjusted" => {
+                match &arg.value {
+                    Value::Ident(flag) if flag == "true" => set_price_option(&mut ctx.adjusted, true, "adjusted", options_set),
+                    Value::Ident(flag) if flag == "false" => set_price_option(&mut ctx.adjusted, false, "adjusted", options_set),
+                    _ => panic!("Expected true or false for 'adjusted', got {:?}", arg.value),
+                }
+            }
+            "currency" => {
+                match &arg.value {
+                    Value::Ident(currency) if ctx.fx_rates.rate(currency).is_some() => {
+                        set_price_option(&mut ctx.currency, Some(currency.to_uppercase()), "currency", options_set);
+                    }
+                    _ => panic!("Unknown currency for 'currency', got {:?}", arg.value),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// a later command may repeat a price option but not change it, the series of all commands share it
+fn set_price_option<T: PartialEq + std::fmt::Debug>(option: &mut T, value: T, name: &'static str, options_set: &mut HashSet<&'static str>) {
+    if !options_set.insert(name) && *option != value {
+        panic!("Conflicting values for '{}': {:?} and {:?}, price options apply to every command of the script", name, option, value);
+    }
+    *option = value;
+}
+
 async fn evaluate_first(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
     evaluate_date_range(ctx, args);
     for arg in args {
@@ -118,18 +199,43 @@ async fn evaluate_first(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
                     Value::List(items) => {
                         for item in items {
                             match item {
+                                // bars of a transform are an item of their own, so the other functions apply to them
+                                Value::FunctionCall(func_call) if is_bar_transform(&func_call.name) => {
+                                    let expr = Expr::FunctionCall(func_call.clone());
+                                    // the transformed item is only an input
+                                    let tracked = (ctx.tracked_items.clone(), ctx.tracked_ids.clone(), ctx.used_series.clone());
+                                    let series = compute_expr_series(ctx, &expr, None).await;
+                                    (ctx.tracked_items, ctx.tracked_ids, ctx.used_series) = tracked;
+                                    let id = expr_to_id(&expr, None);
+                                    if ctx.tracked_ids.insert(id.clone()) {
+                                        ctx.tracked_items.push(TrackedItem {
+                                            id: id.clone(),
+                                            item_type: ItemType::Derived,
+                                        });
+                                    }
+                                    // keyed as an item, the bars are charted as prices and read back by id
+                                    ctx.store_derived(SeriesKey::item(&id), series);
+                                }
                                 Value::FunctionCall(func_call) => {
-                                    evaluate_function_call(ctx, func_call).await;
+                                    let key = evaluate_function_call(ctx, func_call).await;
+                                    // an output picked with a field is charted even if the indicator doesn't draw it
+                                    if func_call.output.is_some() {
+                                        let ids: Vec<String> = ctx.tracked_items.iter().map(|item| item.id.clone()).collect();
+                                        for id in ids {
+                                            ctx.used_series.insert(key.for_item(&id));
+                                        }
+                                    }
                                 }
                                 Value::ArithmeticExpr(expr) => {
                                     // Await async computation of expression series
                                     let series = compute_expr_series(ctx, expr, None).await;
                                     let id = expr_to_id(expr, None);
+                                    let key = SeriesKey::expr(expr, &id);
                                     ctx.tracked_items.push(TrackedItem {
                                         id: id.clone(),
                                         item_type: ItemType::Derived,
                                     });
-                                    ctx.derived_series.insert(id, series);
+                                    ctx.store_derived(key, series);
                                 }
                                 Value::Ident(symbol) => {
                                     println!("Ident found: {}", symbol);
@@ -163,25 +269,161 @@ async fn evaluate_first(ctx: &mut EvalContext, args: &Vec<NamedArg>) {
     }
 }
 
-// by default it checks if function has an argument for item, if not it will calculate the function for all tracked items and return id (RSI_14_)
-pub async fn evaluate_function_call(ctx: &mut EvalContext, func_call: &FunctionCall) -> String {
+fn command_args(command: &Command) -> &Vec<NamedArg> {
+    match command {
+        Command::Filter(args)
+        | Command::Sort(args)
+        | Command::Backtest(args)
+        | Command::Plot(args)
+        | Command::Group(args) => args,
+    }
+}
+
+// symbols referenced in items, function args and expressions of all commands
+fn collect_symbols(program: &Program) -> Vec<String> {
+    let mut symbols = Vec::new();
+    let mut program = program.clone();
+    visit_symbols(&mut program, &mut |symbol| symbols.push(symbol.clone()));
+    symbols
+}
+
+// replaces every symbol with the id its item is stored under and returns the ids,
+// panics on ambiguous symbols
+fn resolve_symbols(program: &Program, directory: &SymbolDirectory) -> (Program, Vec<String>) {
+    let mut program = program.clone();
+    let mut ids = Vec::new();
+    visit_symbols(&mut program, &mut |symbol| {
+        match directory.resolve(symbol) {
+            Ok(id) => {
+                ids.push(id.clone());
+                *symbol = id;
+            }
+            Err(err) => panic!("{}", err),
+        }
+    });
+    (program, ids)
+}
+
+fn visit_symbols(program: &mut Program, f: &mut impl FnMut(&mut String)) {
+    for command in &mut program.commands {
+        let args = match command {
+            Command::Filter(args)
+            | Command::Sort(args)
+            | Command::Backtest(args)
+            | Command::Plot(args)
+            | Command::Group(args) => args,
+        };
+        for arg in args {
+            visit_value_symbols(&mut arg.value, arg.name == "items", f);
+        }
+    }
+}
+
+fn visit_value_symbols(value: &mut Value, is_item: bool, f: &mut impl FnMut(&mut String)) {
+    match value {
+        Value::Ident(symbol) if is_item && !matches!(symbol.as_str(), "stocks" | "indexes" | "all") => f(symbol),
+        Value::List(values) => {
+            for v in values {
+                visit_value_symbols(v, is_item, f);
+            }
+        }
+        Value::FunctionCall(func_call) => visit_function_symbols(func_call, f),
+        Value::ArithmeticExpr(expr) => visit_expr_symbols(expr, f),
+        Value::LogicalExpr(expr) => visit_logical_symbols(expr, f),
+        _ => {}
+    }
+}
+
+fn visit_function_symbols(func_call: &mut FunctionCall, f: &mut impl FnMut(&mut String)) {
+    let indicator = registry().get(&func_call.name);
+    for arg in &mut func_call.args {
+        match arg {
+            FunctionArg::Ident(choice) if indicator.is_some_and(|indicator| is_choice_arg(indicator, choice)) => {}
+            FunctionArg::Ident(symbol) if !is_bar_field(symbol) => f(symbol),
+            FunctionArg::Expr(expr) => visit_expr_symbols(expr, f),
+            _ => {}
+        }
+    }
+}
+
+fn visit_expr_symbols(expr: &mut Expr, f: &mut impl FnMut(&mut String)) {
+    match expr {
+        Expr::Ident(symbol) if !is_bar_field(symbol) && !is_item_field(symbol) => f(symbol),
+        Expr::Ident(_) => {}
+        Expr::FunctionCall(func_call) => visit_function_symbols(func_call, f),
+        Expr::BinaryOp { left, right, .. } => {
+            visit_expr_symbols(left, f);
+            visit_expr_symbols(right, f);
+        }
+        Expr::Group(inner) => visit_expr_symbols(inner, f),
+        Expr::Tuple(values) => {
+            for v in values {
+                visit_value_symbols(v, false, f);
+            }
+        }
+        Expr::Number(_) => {}
+    }
+}
+
+// identifiers in comparisons are fields (market_cap, si, ...), only function args are symbols
+fn visit_logical_symbols(expr: &mut LogicalExpr, f: &mut impl FnMut(&mut String)) {
+    match expr {
+        LogicalExpr::Comparison { left, right, .. } => {
+            for operand in [left, right] {
+                match operand {
+                    Operand::FunctionCall(func_call) => visit_function_symbols(func_call, f),
+                    Operand::LogicalExpr(inner) => visit_logical_symbols(inner, f),
+                    Operand::ArithmeticExpr(expr) => visit_expr_symbols(expr, f),
+                    _ => {}
+                }
+            }
+        }
+        LogicalExpr::BinaryOp { left, right, .. } => {
+            visit_logical_symbols(left, f);
+            visit_logical_symbols(right, f);
+        }
+        LogicalExpr::Group(inner) => visit_logical_symbols(inner, f),
+    }
+}
+
+// by default it checks if function has an argument for item, if not it will calculate the function for all tracked items
+// and return a key without the item (completed per item with `SeriesKey::for_item`)
+pub async fn evaluate_function_call(ctx: &mut EvalContext, func_call: &FunctionCall) -> SeriesKey {
     let name = &func_call.name;
     let args = &func_call.args;
-    
-    handle_calculate_function(ctx, args, &name).await;
-
-    let args_item: Vec<String> = args
-        .iter()
-        .filter_map(|arg| {
-            if let FunctionArg::Ident(item_id) = arg {
-                Some(item_id.to_string())
-            } else {
-                None
-            }
-        })
-        .collect();
 
-    create_function_id(name, args, &args_item.first().unwrap_or(&"".to_string()))
+    match CrossSection::from_name(name) {
+        Some(cross_section) => handle_cross_section(ctx, cross_section, func_call).await,
+        None => handle_calculate_function(ctx, args, name).await,
+    }
+
+    function_key(func_call, &call_item(name, args).unwrap_or_default())
+}
+
+// evaluates the expression of a cross-sectional function for every tracked item and compares the items
+// on each date, the results are reused until the tracked items change
+async fn handle_cross_section(ctx: &mut EvalContext, cross_section: CrossSection, func_call: &FunctionCall) {
+    let expr = CrossSection::expr(func_call);
+    let key = function_key(func_call, "");
+    let items = ctx.tracked_items.clone();
+    let ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+    let keys: Vec<SeriesKey> = ids.iter().map(|id| key.for_item(id)).collect();
+    if ctx.cross_sections.get(&key) == Some(&ids) && keys.iter().all(|k| ctx.derived_series.contains_key(k)) {
+        ctx.used_series.extend(keys);
+        return;
+    }
+
+    // the expression is only an input, functions computed for it are not charted
+    let used_series = ctx.used_series.clone();
+    let mut series = Vec::with_capacity(items.len());
+    for item in &items {
+        series.push(compute_expr_series(ctx, &expr, Some(item)).await);
+    }
+    ctx.used_series = used_series;
+    for (item_key, values) in keys.into_iter().zip(cross_section.apply(&series)) {
+        ctx.store_derived(item_key, values);
+    }
+    ctx.cross_sections.insert(key, ids);
 }
 
 pub fn compute_expr_series<'a>(
@@ -196,6 +438,22 @@ pub fn compute_expr_series<'a>(
                 let to = &ctx.date_range.1;
                 number_series_with_dates(from, to, *val)
             }
+            // close, open, high or low of the item the expression is evaluated for
+            Expr::Ident(field) if tracked_item.is_some() && is_bar_field(field) => {
+                let id = &tracked_item.unwrap().id;
+                match ctx.get_item_prices(id, false).await {
+                    Some(series) => bar_field_series(&series, field),
+                    None => panic!("No series found for item {}", id),
+                }
+            }
+            // current price, change, market_cap, ... of the item, the same value on every date
+            Expr::Ident(field) if is_item_field(field) => match tracked_item {
+                Some(item) => {
+                    let value = item_field_value(ctx, &item.id, field);
+                    number_series_with_dates(&ctx.date_range.0, &ctx.date_range.1, value)
+                }
+                None => panic!("'{}' is a field of an item and can only be used in FILTER or SORT conditions", field),
+            },
             Expr::Ident(symbol) => {
                 if let Some(series) = ctx.get_item_prices(symbol, false).await {
                     series.clone()
@@ -204,16 +462,16 @@ pub fn compute_expr_series<'a>(
                 }
             }
             Expr::FunctionCall(func_call) => {
-                let id_res = evaluate_function_call(ctx, func_call).await;
-                let id = if let Some(tracked_item) = tracked_item {
-                    id_res + &tracked_item.id
+                let key_res = evaluate_function_call(ctx, func_call).await;
+                let key = if let Some(tracked_item) = tracked_item {
+                    key_res.for_item(&tracked_item.id)
                 } else {
-                    id_res
+                    key_res
                 };
-                if let Some(series) = ctx.derived_series.get(&id) {
+                if let Some(series) = ctx.derived_series.get(&key) {
                     series.clone()
                 } else {
-                    panic!("No series found for function call {}", id);
+                    panic!("No series found for function call {}", key);
                 }
             }
             Expr::BinaryOp { left, op, right } => {
@@ -264,6 +522,12 @@ pub fn apply_arithmetic_op(
                     safe_div(val_left.2, val_right.2),
                     safe_div(val_left.3, val_right.3),
                 ),
+                ArithmeticOp::Mul => (
+                    val_left.0 * val_right.0,
+                    val_left.1 * val_right.1,
+                    val_left.2 * val_right.2,
+                    val_left.3 * val_right.3,
+                ),
             };
 
             result.push(((*date).clone(), combined));
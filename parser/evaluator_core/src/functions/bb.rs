@@ -1,8 +1,12 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::ma::sma;
 
-// Bollinger Bands (Middle, Upper, Lower, Width)
+
+// Bollinger Bands (Middle, Upper, Lower, Width), bands are `mult` standard deviations from the middle
 pub fn bollinger_bands(
     prices: &Vec<(String, (f64, f64, f64, f64))>, 
-    period: usize
+    period: usize,
+    mult: f64,
 ) -> Vec<(String, (f64, f64, f64, f64))> {
     if prices.len() < period {
         return vec![];
@@ -18,8 +22,8 @@ pub fn bollinger_bands(
         let sma = window.iter().sum::<f64>() / period as f64;
         let std_dev = calculate_standard_deviation(window, sma);
 
-        let upper = sma + 2.0 * std_dev;
-        let lower = sma - 2.0 * std_dev;
+        let upper = sma + mult * std_dev;
+        let lower = sma - mult * std_dev;
         let width = ((upper - lower) / sma) * 100.0;
 
         bands.push((
@@ -120,3 +124,95 @@ pub fn bbwp(
 
     out
 }
+
+
+pub struct Bb;
+
+impl Indicator for Bb {
+    fn name(&self) -> &'static str {
+        "BB"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20), ParamSpec::number("mult", 2.0)];
+        PARAMS
+    }
+
+    // width is in % of the middle band, pctb is where the close is between the bands (0 lower, 1 upper)
+    fn outputs(&self) -> &'static [&'static str] {
+        &["middle", "upper", "lower", "width", "pctb"]
+    }
+
+    fn output_panel(&self, output: &str) -> Panel {
+        match output {
+            "width" | "pctb" => Panel::Separate,
+            _ => Panel::Price,
+        }
+    }
+
+    fn charted_outputs(&self) -> &'static [&'static str] {
+        &["middle", "upper", "lower"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let bands = bollinger_bands(input.prices, params.length("period"), params.number("mult"));
+        let closes = &input.prices[input.prices.len() - bands.len()..];
+
+        let mut outputs: Vec<Series> = (0..5).map(|_| Vec::with_capacity(bands.len())).collect();
+        for ((date, (middle, upper, lower, width)), (_, price)) in bands.into_iter().zip(closes) {
+            let pctb = if upper > lower { (price.0 - lower) / (upper - lower) } else { 0.5 };
+            for (output, value) in outputs.iter_mut().zip([middle, upper, lower, width, pctb]) {
+                output.push((date.clone(), (value, 0.0, 0.0, 0.0)));
+            }
+        }
+        outputs
+    }
+}
+
+pub struct Bbwp;
+
+impl Indicator for Bbwp {
+    fn name(&self) -> &'static str {
+        "BBWP"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 13), ParamSpec::length("lookback", 252)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![bbwp(input.prices, params.length("period"), params.length("lookback"))]
+    }
+}
+
+// moving average of the BBWP
+pub struct BbwpMa;
+
+impl Indicator for BbwpMa {
+    fn name(&self) -> &'static str {
+        "BBWPMA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[
+            ParamSpec::length("period", 13),
+            ParamSpec::length("lookback", 252),
+            ParamSpec::length("ma_period", 14),
+        ];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let bbwp = bbwp(input.prices, params.length("period"), params.length("lookback"));
+        vec![sma(&bbwp, params.length("ma_period") as u64)]
+    }
+}
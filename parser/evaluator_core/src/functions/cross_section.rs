@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use parser_core::ast::{Expr, FunctionArg, FunctionCall};
+use crate::functions::indicator::{Panel, Series};
+
+/// Functions that compare the value of an expression across all tracked items on each date
+/// (`PCTRANK(ROC(126)) >= 80`), as opposed to indicators that only see one item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossSection {
+    // 1 for the highest value, ties share the better rank
+    Rank,
+    // % of the other items with a lower value, 100 for the highest
+    PctRank,
+    // distance from the mean of all items in standard deviations
+    Zscore,
+    // 1 for the lowest tenth of the items to 10 for the highest
+    Decile,
+}
+
+impl CrossSection {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "RANK" => Some(CrossSection::Rank),
+            "PCTRANK" => Some(CrossSection::PctRank),
+            "ZSCORE_XS" => Some(CrossSection::Zscore),
+            "DECILE" => Some(CrossSection::Decile),
+            _ => None,
+        }
+    }
+
+    /// The expression a call compares, `RANK(close)` and `RANK(ROC(126))` alike.
+    pub fn expr(func_call: &FunctionCall) -> Expr {
+        match func_call.args.as_slice() {
+            [FunctionArg::Expr(expr)] => expr.clone(),
+            [FunctionArg::Ident(ident)] => Expr::Ident(ident.clone()),
+            _ => panic!("{} takes one expression, e.g. {}(ROC(126))", func_call.name, func_call.name),
+        }
+    }
+
+    pub fn panel(&self) -> Panel {
+        match self {
+            CrossSection::PctRank => Panel::Range(0.0, 100.0),
+            _ => Panel::Separate,
+        }
+    }
+
+    // values of all items on one date, in the same order
+    fn compute(&self, values: &[f64]) -> Vec<f64> {
+        let n = values.len();
+        let lower = |v: f64| values.iter().filter(|other| **other < v).count();
+        let pct_rank = |v: f64| if n > 1 { lower(v) as f64 / (n - 1) as f64 * 100.0 } else { 100.0 };
+
+        match self {
+            CrossSection::Rank => values
+                .iter()
+                .map(|v| (values.iter().filter(|other| **other > *v).count() + 1) as f64)
+                .collect(),
+            CrossSection::PctRank => values.iter().map(|v| pct_rank(*v)).collect(),
+            CrossSection::Zscore => {
+                let mean = values.iter().sum::<f64>() / n as f64;
+                let sd = match n {
+                    0 | 1 => 0.0,
+                    _ => (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt(),
+                };
+                values.iter().map(|v| if sd > 0.0 { (v - mean) / sd } else { 0.0 }).collect()
+            }
+            CrossSection::Decile => values.iter().map(|v| (pct_rank(*v) / 10.0).floor().clamp(0.0, 9.0) + 1.0).collect(),
+        }
+    }
+
+    /// Series of every item from the series of the expression per item, on each date only
+    /// the items with a value that day are compared.
+    pub fn apply(&self, series: &[Series]) -> Vec<Series> {
+        let mut dates: BTreeMap<&str, Vec<(usize, f64)>> = BTreeMap::new();
+        for (idx, values) in series.iter().enumerate() {
+            for (date, v) in values {
+                dates.entry(date.as_str()).or_default().push((idx, v.0));
+            }
+        }
+
+        let mut result: Vec<Series> = vec![Vec::new(); series.len()];
+        for (date, items) in dates {
+            let values: Vec<f64> = items.iter().map(|(_, v)| *v).collect();
+            for ((idx, _), value) in items.iter().zip(self.compute(&values)) {
+                result[*idx].push((date.to_string(), (value, 0.0, 0.0, 0.0)));
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::test_data::assert_close;
+
+    fn series(points: &[(&str, f64)]) -> Series {
+        points.iter().map(|(date, v)| (date.to_string(), (*v, 0.0, 0.0, 0.0))).collect()
+    }
+
+    fn assert_values(actual: Vec<f64>, expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?}, expected {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn ties_share_the_better_rank() {
+        let values = [3.0, 1.0, 3.0, 2.0];
+        assert_values(CrossSection::Rank.compute(&values), &[1.0, 4.0, 1.0, 3.0]);
+        assert_values(CrossSection::PctRank.compute(&values), &[200.0 / 3.0, 0.0, 200.0 / 3.0, 100.0 / 3.0]);
+        assert_values(CrossSection::Decile.compute(&values), &[7.0, 1.0, 7.0, 4.0]);
+        assert_values(
+            CrossSection::Zscore.compute(&values),
+            &[0.7833494518006403, -1.3055824196677337, 0.7833494518006403, -0.26111648393354675],
+        );
+    }
+
+    #[test]
+    fn highest_value_is_in_the_top_decile() {
+        assert_values(CrossSection::Decile.compute(&[1.0, 2.0, 3.0]), &[1.0, 6.0, 10.0]);
+    }
+
+    #[test]
+    fn single_item_is_the_best() {
+        assert_values(CrossSection::Rank.compute(&[5.0]), &[1.0]);
+        assert_values(CrossSection::PctRank.compute(&[5.0]), &[100.0]);
+        assert_values(CrossSection::Decile.compute(&[5.0]), &[10.0]);
+        assert_values(CrossSection::Zscore.compute(&[5.0]), &[0.0]);
+    }
+
+    #[test]
+    fn equal_values_have_no_zscore() {
+        assert_values(CrossSection::Zscore.compute(&[2.0, 2.0, 2.0]), &[0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn apply_compares_only_items_with_a_value_that_day() {
+        let a = series(&[("2024-01-02", 1.0), ("2024-01-03", 2.0)]);
+        let b = series(&[("2024-01-02", 3.0)]);
+        let c = series(&[("2024-01-03", 1.0), ("2024-01-04", 5.0)]);
+        let ranks = CrossSection::Rank.apply(&[a, b, c]);
+
+        assert_eq!(ranks[0].len(), 2);
+        assert_close(&ranks[0][0], "2024-01-02", 2.0);
+        assert_close(&ranks[0][1], "2024-01-03", 1.0);
+        assert_eq!(ranks[1].len(), 1);
+        assert_close(&ranks[1][0], "2024-01-02", 1.0);
+        assert_eq!(ranks[2].len(), 2);
+        assert_close(&ranks[2][0], "2024-01-03", 2.0);
+        assert_close(&ranks[2][1], "2024-01-04", 1.0);
+    }
+
+    #[test]
+    fn apply_to_a_single_item() {
+        let pct = CrossSection::PctRank.apply(&[series(&[("2024-01-02", -4.0), ("2024-01-03", 7.0)])]);
+        assert_eq!(pct.len(), 1);
+        assert_close(&pct[0][0], "2024-01-02", 100.0);
+        assert_close(&pct[0][1], "2024-01-03", 100.0);
+    }
+}
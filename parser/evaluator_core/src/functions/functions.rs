@@ -1,21 +1,29 @@
 use crate::context::EvalContext;
-use crate::functions::bb::*;
-use crate::functions::ma::*;
-use crate::functions::rsi::*;
-use crate::helpers::{create_function_id};
-use parser_core::ast::FunctionArg;
-use crate::response_types::ItemType;
+use crate::functions::cross_section::CrossSection;
+use crate::functions::indicator::{is_choice_arg, resolve_params, Indicator, IndicatorInput, Panel, Params};
+use crate::functions::registry::registry;
+use crate::functions::relative::home_benchmark;
+use crate::helpers::{expr_label, function_params};
+use crate::series_key::SeriesKey;
+use parser_core::ast::{FunctionArg, FunctionCall};
+use crate::response_types::{ChartType, Item, ItemType};
 
 pub async fn handle_calculate_function(ctx: &mut EvalContext, args: &Vec<FunctionArg>, name: &String) {
+    let indicator = match registry().get(name) {
+        Some(indicator) => indicator,
+        None => {
+            println!("Unknown function: {}", name);
+            return;
+        }
+    };
+    let params = match resolve_params(indicator, args) {
+        Ok(params) => params,
+        Err(err) => panic!("{}", err),
+    };
+
     // get ids that need to be calculated
     // first check if there are ids in args provided
-    let mut ids = args.iter().filter_map(|arg| {
-        if let FunctionArg::Ident(ident) = arg {
-            Some(ident.clone())
-        } else {
-            None
-        }
-    }).collect::<Vec<String>>();
+    let (mut ids, benchmark) = call_symbols(indicator, args);
     // if no ids provided, use all tracked items
     if ids.is_empty() {
         ids = ctx.tracked_items.iter()
@@ -28,102 +36,165 @@ pub async fn handle_calculate_function(ctx: &mut EvalContext, args: &Vec<Functio
     }
 
     for id in ids {
-        // id for function cache
-        let key = create_function_id(&name, &args, &id);
+        // key for function cache
+        let key = SeriesKey::function(name, call_key_params(benchmark.as_ref(), &params), &id);
+        // outputs the indicator draws when charted, the others are only there for filters and sorts
+        let charted_keys: Vec<SeriesKey> = indicator
+            .charted_outputs()
+            .iter()
+            .map(|output| output_key(indicator, &key, output))
+            .collect();
         // check if the function is already calculated
         if ctx.derived_series.contains_key(&key) {
+            ctx.used_series.extend(charted_keys);
             continue;
+        }
+
+        let prices = match ctx.get_item_prices(&id, true).await {
+            Some(prices) => prices,
+            None => panic!("No prices found for item: {}", id),
+        };
+        // already adjusted prices must not be adjusted twice
+        let actions = if indicator.uses_actions() && !ctx.adjusted {
+            ctx.get_corporate_actions(&id).await
         } else {
-            let prices = ctx.get_item_prices(&id, true).await;
-            match prices {
-                Some(prices) => {
-                    // calculate the function
-                    let result = match name.as_str() {
-                        "RSI" => {
-                            let len: u64 = match args[0] {
-                                FunctionArg::Number(n) => n as u64,
-                                _ => 14,
-                            };
-                            rsi(&prices, len)
-                        }
-                        "RSIMA" => {
-                            let rsi_len: u64 = match args[0] {
-                                FunctionArg::Number(n) => n as u64,
-                                _ => 14,
-                            };
-                            let ma_len =  match args[1] {
-                                FunctionArg::Number(n) => n as u64,
-                                _ => 14,
-                            };
-                            let rsi = rsi(&prices, rsi_len);
-                            sma(&rsi, ma_len)
-                        }
-                        "MA" => {
-                            let len: u64 = match args[0] {
-                                FunctionArg::Number(n) => n as u64,
-                                _ => 14,
-                            };
-                            sma(&prices, len)
-                        }
-                        "EMA" => {
-                            let len: u64 = match args[0] {
-                                FunctionArg::Number(n) => n as u64,
-                                _ => 14,
-                            };
-                            ema(&prices, len)
-                        }
-                        "WMA" => {
-                            let len: u64 = match args[0] {
-                                FunctionArg::Number(n) => n as u64,
-                                _ => 14,
-                            };
-                            wma(&prices, len)
-                        }
-                        "BBWP" => {
-                            let bbw_len: usize = match args[0] {
-                                FunctionArg::Number(n) => n as usize,
-                                _ => 13,
-                            };
-                            let lookback: usize = match args[1] {
-                                FunctionArg::Number(n) => n as usize,
-                                _ => 252,
-                            };
-                            bbwp(&prices, bbw_len, lookback)
-                        }
-                        "BBWPMA" => {
-                            let bbw_len: usize = match args[0] {
-                                FunctionArg::Number(n) => n as usize,
-                                _ => 13,
-                            };
-                            let lookback: usize = match args[1] {
-                                FunctionArg::Number(n) => n as usize,
-                                _ => 252,
-                            };
-                            let ma_len =  match args[1] {
-                                FunctionArg::Number(n) => n as u64,
-                                _ => 14,
-                            };
-                            let bbwp = bbwp(&prices, bbw_len, lookback);
-                            sma(&bbwp, ma_len)
-                        }
-                        _ => {
-                            println!("Unknown function: {}", name);
-                            continue;
-                        }
-                    };
-
-                    // store the result in the context
-                    ctx.derived_series.insert(key, result);
+            Vec::new()
+        };
+
+        let bars = match indicator.uses_volume() {
+            true => ctx.price_series.get(&id).cloned().unwrap_or_default(),
+            false => Vec::new(),
+        };
+
+        let benchmark_prices = match indicator.uses_benchmark() {
+            true => {
+                let benchmark = benchmark.clone().unwrap_or_else(|| home_benchmark_id(ctx, &id, name));
+                match ctx.get_item_prices(&benchmark, false).await {
+                    Some(prices) => prices,
+                    None => panic!("No prices found for benchmark: {}", benchmark),
                 }
-                None => panic!("No prices found for item: {}", id),
             }
+            false => Vec::new(),
+        };
+
+        // calculate the function and store every output in the context
+        let input = IndicatorInput { prices: &prices, actions: &actions, bars: &bars, benchmark: &benchmark_prices };
+        for (output, series) in indicator.outputs().iter().zip(indicator.compute(&input, &params)) {
+            ctx.derived_series.insert(output_key(indicator, &key, output), series);
         }
+        ctx.used_series.extend(charted_keys);
     }
 }
 
-pub fn indicator_to_panel_id(string: &str) -> i32 {
-    match string.split('_').next().unwrap_or("") {
-        "RSI" | "RSIMA" | "BBWP" | "BBWPMA" => 1,   // list of indicators that range from 0 to 100 (are in separate panel)
-        _ => 0,                 // default panel for other indicators (all lines that go on candles)
+/// Items and benchmark named in the args of a call. Indicators with a benchmark read a single
+/// symbol as the benchmark (`RS(SBITOP, 50)`) and two as item and benchmark (`RS(KRKG, SBITOP, 50)`).
+pub fn call_symbols(indicator: &dyn Indicator, args: &[FunctionArg]) -> (Vec<String>, Option<String>) {
+    let mut symbols: Vec<String> = args
+        .iter()
+        .filter_map(|arg| match arg {
+            FunctionArg::Ident(ident) if !is_choice_arg(indicator, ident) => Some(ident.clone()),
+            _ => None,
+        })
+        .collect();
+
+    match indicator.uses_benchmark() {
+        true if symbols.len() > 2 => panic!("{} takes an item and a benchmark, got {}", indicator.name(), symbols.join(", ")),
+        true => {
+            let benchmark = symbols.pop();
+            (symbols, benchmark)
+        }
+        false => (symbols, None),
+    }
+}
+
+// item of a call to `name`, None when it is computed for the tracked items
+pub fn call_item(name: &str, args: &[FunctionArg]) -> Option<String> {
+    if CrossSection::from_name(name).is_some() {
+        return None;
     }
+    match registry().get(name) {
+        Some(indicator) => call_symbols(indicator, args).0.into_iter().next(),
+        None => args.iter().find_map(|arg| match arg {
+            FunctionArg::Ident(ident) => Some(ident.clone()),
+            _ => None,
+        }),
+    }
+}
+
+// a benchmark named in the call is part of the key, the default one follows from the item
+fn call_key_params(benchmark: Option<&String>, params: &Params) -> Vec<String> {
+    benchmark.into_iter().cloned().chain(params.key_params()).collect()
+}
+
+// id of the index of the item's market
+fn home_benchmark_id(ctx: &mut EvalContext, id: &str, name: &str) -> String {
+    let mic = match ctx.get_item_data(id) {
+        Some(Item::Stock(stock)) => stock.mic,
+        Some(Item::Index(index)) => index.mic,
+        None => String::new(),
+    };
+    match (home_benchmark(&mic), &ctx.directory) {
+        (Some(symbol), Some(directory)) => directory.item_id(&mic, symbol),
+        (Some(symbol), None) => symbol.to_string(),
+        (None, _) => panic!("No benchmark index for {}, name one e.g. {}(SBITOP)", id, name),
+    }
+}
+
+// the first output is stored under the plain call, the others under their name
+fn output_key(indicator: &dyn Indicator, key: &SeriesKey, output: &str) -> SeriesKey {
+    if indicator.outputs().first() == Some(&output) {
+        key.clone()
+    } else {
+        key.with_output(output)
+    }
+}
+
+/// Key of a function call on `item`, params are completed with the indicator's defaults.
+/// A field (`MACD(12,26,9).hist`) selects one output of a multi-output indicator.
+pub fn function_key(func_call: &FunctionCall, item: &str) -> SeriesKey {
+    let indicator = registry().get(&func_call.name);
+    let params = match indicator.map(|indicator| (indicator, resolve_params(indicator, &func_call.args))) {
+        Some((indicator, Ok(params))) => call_key_params(call_symbols(indicator, &func_call.args).1.as_ref(), &params),
+        _ if CrossSection::from_name(&func_call.name).is_some() => vec![expr_label(&CrossSection::expr(func_call))],
+        _ => function_params(&func_call.args),
+    };
+    let key = SeriesKey::function(&func_call.name, params, item);
+
+    match (&func_call.output, indicator) {
+        (None, _) => key,
+        (Some(output), Some(indicator)) => match indicator.outputs().contains(&output.as_str()) {
+            true => output_key(indicator, &key, output),
+            false => panic!(
+                "{} has no output {}, use one of: {}",
+                func_call.name,
+                output,
+                indicator.outputs().join(", ")
+            ),
+        },
+        (Some(output), None) => key.with_output(output),
+    }
+}
+
+// indicator of a function key and the output the key stands for
+fn key_output(key: &SeriesKey) -> Option<(&'static dyn Indicator, &str)> {
+    let indicator = registry().get(key.function.as_deref()?)?;
+    Some((indicator, key.output.as_deref().unwrap_or(indicator.outputs()[0])))
+}
+
+pub fn indicator_panel(key: &SeriesKey) -> Panel {
+    if let Some(cross_section) = key.function.as_deref().and_then(CrossSection::from_name) {
+        return cross_section.panel();
+    }
+    key_output(key).map_or(Panel::Price, |(indicator, output)| indicator.output_panel(output))
+}
+
+/// Functions returning bars (`HA(KRKG)`) rather than values, drawn as candles.
+pub fn is_bar_transform(name: &str) -> bool {
+    registry()
+        .get(name)
+        .is_some_and(|indicator| matches!(indicator.chart_type(indicator.outputs()[0]), ChartType::Price))
+}
+
+pub fn indicator_chart_type(key: &SeriesKey) -> ChartType {
+    key_output(key).map_or(ChartType::Indicator, |(indicator, output)| indicator.chart_type(output))
 }
@@ -0,0 +1,301 @@
+use parser_core::ast::FunctionArg;
+use crate::helpers::expr_label;
+use crate::response_types::ChartType;
+use crate::types::{CorporateAction, DailyPrice};
+
+pub type Point = (String, (f64, f64, f64, f64));    // date, (CLOSE, OPEN, HIGH, LOW)
+pub type Series = Vec<Point>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamKind {
+    // positive whole number (periods, lookbacks)
+    Length,
+    // positive number (multipliers, steps)
+    Number,
+    // 1d, 1w, 1m, 1q
+    Duration,
+    // one of a fixed set of names (classic, fibonacci)
+    Choice,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Number(f64),
+    Duration(String),
+    Choice(String),
+}
+
+impl ParamValue {
+    // how the value appears in series ids (RSI_14_KRKG)
+    fn to_key(&self) -> String {
+        match self {
+            ParamValue::Number(n) => n.to_string(),
+            ParamValue::Duration(d) => d.clone(),
+            ParamValue::Choice(c) => c.clone(),
+        }
+    }
+}
+
+/// One positional parameter of an indicator, used when the script leaves it out.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: f64,
+    // default of duration and choice params
+    pub default_text: &'static str,
+    // names a choice param accepts
+    pub choices: &'static [&'static str],
+    // smallest value of a length param
+    pub min: f64,
+}
+
+impl ParamSpec {
+    pub const fn length(name: &'static str, default: usize) -> Self {
+        Self { name, kind: ParamKind::Length, default: default as f64, default_text: "", choices: &[], min: 1.0 }
+    }
+
+    pub const fn number(name: &'static str, default: f64) -> Self {
+        Self { name, kind: ParamKind::Number, default, default_text: "", choices: &[], min: 1.0 }
+    }
+
+    pub const fn duration(name: &'static str, default: &'static str) -> Self {
+        Self { name, kind: ParamKind::Duration, default: 0.0, default_text: default, choices: &[], min: 1.0 }
+    }
+
+    pub const fn choice(name: &'static str, choices: &'static [&'static str], default: &'static str) -> Self {
+        Self { name, kind: ParamKind::Choice, default: 0.0, default_text: default, choices, min: 1.0 }
+    }
+
+    /// A length param accepting values down to `min` (`BETA` needs two returns).
+    pub const fn at_least(self, min: usize) -> Self {
+        Self { min: min as f64, ..self }
+    }
+
+    fn default_value(&self) -> ParamValue {
+        match self.kind {
+            ParamKind::Duration => ParamValue::Duration(self.default_text.to_string()),
+            ParamKind::Choice => ParamValue::Choice(self.default_text.to_string()),
+            _ => ParamValue::Number(self.default),
+        }
+    }
+}
+
+/// Parameter values of one call, in the order of the indicator's `ParamSpec`s.
+#[derive(Debug, Clone)]
+pub struct Params {
+    names: Vec<&'static str>,
+    values: Vec<ParamValue>,
+}
+
+impl Params {
+    fn get(&self, name: &str) -> &ParamValue {
+        match self.names.iter().position(|n| *n == name) {
+            Some(idx) => &self.values[idx],
+            None => panic!("Indicator has no parameter {}", name),
+        }
+    }
+
+    pub fn number(&self, name: &str) -> f64 {
+        match self.get(name) {
+            ParamValue::Number(n) => *n,
+            ParamValue::Duration(text) | ParamValue::Choice(text) => panic!("Parameter {} is not a number ({})", name, text),
+        }
+    }
+
+    pub fn length(&self, name: &str) -> usize {
+        self.number(name) as usize
+    }
+
+    pub fn duration(&self, name: &str) -> &str {
+        match self.get(name) {
+            ParamValue::Duration(d) => d,
+            other => panic!("Parameter {} is not a duration ({})", name, other.to_key()),
+        }
+    }
+
+    pub fn choice(&self, name: &str) -> &str {
+        match self.get(name) {
+            ParamValue::Choice(c) => c,
+            other => panic!("Parameter {} is not a choice ({})", name, other.to_key()),
+        }
+    }
+
+    /// Values as they appear in series keys, defaults included so `RSI()` and `RSI(14)` share results.
+    pub fn key_params(&self) -> Vec<String> {
+        self.values.iter().map(|v| v.to_key()).collect()
+    }
+}
+
+/// Where the frontend draws the outputs of an indicator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Panel {
+    // over the candles
+    Price,
+    // oscillator with a fixed range, 0–100 ones share a panel
+    Range(f64, f64),
+    // unbounded values on their own panel
+    Separate,
+}
+
+impl Panel {
+    /// 0 for the price panel, 1 for 0–100 oscillators, indicators with other ranges
+    /// get 2 here and are moved to a panel of their own when charts are created.
+    pub fn panel_id(&self) -> i32 {
+        match self {
+            Panel::Price => 0,
+            Panel::Range(lo, hi) if *lo == 0.0 && *hi == 100.0 => 1,
+            Panel::Range(_, _) | Panel::Separate => 2,
+        }
+    }
+}
+
+/// Data an indicator is computed from.
+pub struct IndicatorInput<'a> {
+    pub prices: &'a Series,
+    // corporate actions of the item, only loaded for indicators with `uses_actions`
+    pub actions: &'a [CorporateAction],
+    // bars of `prices` with volume and turnover, only loaded for indicators with `uses_volume`
+    // and empty for indexes and derived items
+    pub bars: &'a [DailyPrice],
+    // prices of the benchmark index, only loaded for indicators with `uses_benchmark`
+    pub benchmark: &'a Series,
+}
+
+/// A function callable from scripts (`RSI(14)`, `BB(20, 2)`). Implementations declare
+/// their parameters and outputs, the evaluator validates calls and stores the results.
+pub trait Indicator: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn params(&self) -> &'static [ParamSpec];
+
+    /// Names of the output columns, the first one is the value of the plain call.
+    fn outputs(&self) -> &'static [&'static str] {
+        &["value"]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Price
+    }
+
+    /// Panel of one output, for outputs on a different scale than the rest (BB(20,2).width).
+    fn output_panel(&self, _output: &str) -> Panel {
+        self.panel()
+    }
+
+    /// Outputs drawn when the indicator is charted, all of them unless the indicator says otherwise.
+    /// The others are still computed and can be used in filters, sorts or charted with a field.
+    fn charted_outputs(&self) -> &'static [&'static str] {
+        self.outputs()
+    }
+
+    /// How an output is drawn, lines unless the indicator says otherwise.
+    fn chart_type(&self, _output: &str) -> ChartType {
+        ChartType::Indicator
+    }
+
+    fn uses_actions(&self) -> bool {
+        false
+    }
+
+    fn uses_volume(&self) -> bool {
+        false
+    }
+
+    /// Compared against a benchmark index, the symbol of the call is the benchmark
+    /// (`RS(SBITOP, 50)`) and the item's home index is used when there is none.
+    fn uses_benchmark(&self) -> bool {
+        false
+    }
+
+    /// One series per output, in the order of `outputs`.
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series>;
+}
+
+/// Whether an ident arg of a call is the value of a choice param (`PIVOTS(fibonacci)`) rather than a symbol.
+pub fn is_choice_arg(indicator: &dyn Indicator, ident: &str) -> bool {
+    indicator.params().iter().any(|spec| spec.choices.contains(&ident))
+}
+
+/// Matches the numeric, duration and choice args of a call against the parameter schema.
+/// Missing trailing args take their defaults, extra or mistyped args are an error.
+pub fn resolve_params(indicator: &dyn Indicator, args: &[FunctionArg]) -> Result<Params, String> {
+    // only cross-sectional functions (RANK(ROC(126))) take an expression, they are not indicators
+    if let Some(FunctionArg::Expr(expr)) = args.iter().find(|arg| matches!(arg, FunctionArg::Expr(_))) {
+        return Err(format!(
+            "{} does not take an expression argument, got {}",
+            indicator.name(),
+            expr_label(expr)
+        ));
+    }
+
+    let specs = indicator.params();
+    let given: Vec<&FunctionArg> = args
+        .iter()
+        .filter(|arg| match arg {
+            FunctionArg::Number(_) | FunctionArg::Duration(_) => true,
+            FunctionArg::Ident(ident) => is_choice_arg(indicator, ident),
+            _ => false,
+        })
+        .collect();
+
+    if given.len() > specs.len() {
+        return Err(format!(
+            "{} takes at most {} parameter(s) ({}), got {}",
+            indicator.name(),
+            specs.len(),
+            specs.iter().map(|s| s.name).collect::<Vec<_>>().join(", "),
+            given.len()
+        ));
+    }
+
+    let mut values = Vec::with_capacity(specs.len());
+    for (idx, spec) in specs.iter().enumerate() {
+        let value = match (given.get(idx), spec.kind) {
+            (None, _) => spec.default_value(),
+            (Some(FunctionArg::Number(n)), ParamKind::Length) if *n >= spec.min && n.fract() == 0.0 => ParamValue::Number(*n),
+            (Some(FunctionArg::Number(n)), ParamKind::Number) if *n > 0.0 => ParamValue::Number(*n),
+            (Some(FunctionArg::Duration(d)), ParamKind::Duration) => ParamValue::Duration(d.clone()),
+            (Some(FunctionArg::Ident(c)), ParamKind::Choice) if spec.choices.contains(&c.as_str()) => ParamValue::Choice(c.clone()),
+            (Some(arg), kind) => {
+                let expected = match kind {
+                    ParamKind::Length if spec.min == 1.0 => "a positive whole number".to_string(),
+                    ParamKind::Length => format!("a whole number of at least {}", spec.min),
+                    ParamKind::Number => "a positive number".to_string(),
+                    ParamKind::Duration => "a duration (1d, 1w, 1m, 1q)".to_string(),
+                    ParamKind::Choice => format!("one of {}", spec.choices.join(", ")),
+                };
+                let got = match arg {
+                    FunctionArg::Number(n) => n.to_string(),
+                    FunctionArg::Duration(d) | FunctionArg::Ident(d) => d.clone(),
+                    other => format!("{:?}", other),
+                };
+                return Err(format!("{}: {} must be {}, got {}", indicator.name(), spec.name, expected, got));
+            }
+        };
+        values.push(value);
+    }
+
+    Ok(Params {
+        names: specs.iter().map(|s| s.name).collect(),
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::registry::registry;
+    use parser_core::ast::{Expr, FunctionCall};
+
+    #[test]
+    fn expression_arguments_are_rejected() {
+        let rsi = registry().get("RSI").unwrap();
+        let roc = FunctionCall { name: "ROC".to_string(), args: vec![FunctionArg::Number(5.0)], output: None };
+        let err = resolve_params(rsi, &[FunctionArg::Expr(Expr::FunctionCall(roc))]).err().unwrap();
+        assert_eq!(err, "RSI does not take an expression argument, got ROC_5");
+
+        let params = resolve_params(rsi, &[FunctionArg::Ident("KRKG".to_string()), FunctionArg::Number(7.0)]).unwrap();
+        assert_eq!(params.length("period"), 7);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::volatility::bar_range;
+use crate::resample::bucket_key;
+use crate::types::Timeframe;
+
+const PIVOT_TYPES: &[&str] = &["classic", "fibonacci", "camarilla"];
+
+// pp, r1, r2, r3, s1, s2, s3 from the high, low and close of the previous period
+fn pivot_levels(kind: &str, high: f64, low: f64, close: f64) -> [f64; 7] {
+    let pp = (high + low + close) / 3.0;
+    let range = high - low;
+    match kind {
+        "fibonacci" => [
+            pp,
+            pp + 0.382 * range,
+            pp + 0.618 * range,
+            pp + range,
+            pp - 0.382 * range,
+            pp - 0.618 * range,
+            pp - range,
+        ],
+        "camarilla" => [
+            pp,
+            close + range * 1.1 / 12.0,
+            close + range * 1.1 / 6.0,
+            close + range * 1.1 / 4.0,
+            close - range * 1.1 / 12.0,
+            close - range * 1.1 / 6.0,
+            close - range * 1.1 / 4.0,
+        ],
+        _ => [
+            pp,
+            2.0 * pp - low,
+            pp + range,
+            high + 2.0 * (pp - low),
+            2.0 * pp - high,
+            pp - range,
+            low - 2.0 * (high - pp),
+        ],
+    }
+}
+
+/// Pivot point levels of every bar, computed from the previous `timeframe` period, in the order
+/// pp, r1, r2, r3, s1, s2, s3. Bars of the first period have no previous one and no levels.
+pub fn pivots(prices: &Series, kind: &str, timeframe: Timeframe) -> Vec<Series> {
+    let mut result: Vec<Series> = vec![Vec::new(); 7];
+    // (bucket, high, low, close) of the current and the previous period
+    let mut current: Option<((i32, u32), f64, f64, f64)> = None;
+    let mut previous: Option<(f64, f64, f64)> = None;
+    for (date, bar) in prices {
+        let bucket = bucket_key(date, timeframe);
+        let (high, low) = bar_range(bar);
+        current = match current {
+            Some((key, h, l, _)) if key == bucket => Some((key, h.max(high), l.min(low), bar.0)),
+            Some((_, h, l, c)) => {
+                previous = Some((h, l, c));
+                Some((bucket, high, low, bar.0))
+            }
+            None => Some((bucket, high, low, bar.0)),
+        };
+
+        if let Some((h, l, c)) = previous {
+            for (series, level) in result.iter_mut().zip(pivot_levels(kind, h, l, c)) {
+                series.push((date.clone(), (level, 0.0, 0.0, 0.0)));
+            }
+        }
+    }
+
+    result
+}
+
+// (bar index, level) of the swing highs or lows, a bar whose high (low) is the extreme of the
+// `n` bars on both sides, the first of equal extremes counts
+fn swings(prices: &Series, n: usize, high: bool) -> Vec<(usize, f64)> {
+    let levels: Vec<f64> = prices
+        .iter()
+        .map(|(_, bar)| {
+            let (h, l) = bar_range(bar);
+            if high { h } else { l }
+        })
+        .collect();
+    // a above b in the direction of the swing
+    let beyond = |a: f64, b: f64| if high { a > b } else { a < b };
+
+    (n..levels.len().saturating_sub(n))
+        .filter(|i| {
+            let level = levels[*i];
+            levels[i - n..*i].iter().all(|v| beyond(level, *v)) && levels[i + 1..=i + n].iter().all(|v| !beyond(*v, level))
+        })
+        .map(|i| (i, levels[i]))
+        .collect()
+}
+
+/// Level of the last swing high (low) of `n` bars, from the bar that confirms it `n` bars later.
+pub fn swing_level(prices: &Series, n: usize, high: bool) -> Series {
+    let swings = swings(prices, n, high);
+    let Some(first) = swings.first() else {
+        return vec![];
+    };
+
+    let mut next = swings.iter().peekable();
+    let mut level = first.1;
+    (first.0 + n..prices.len())
+        .map(|i| {
+            while let Some((_, swing)) = next.next_if(|(idx, _)| idx + n <= i) {
+                level = *swing;
+            }
+            (prices[i].0.clone(), (level, 0.0, 0.0, 0.0))
+        })
+        .collect()
+}
+
+/// Distance of the close to the nearest swing high above it (swing low below it for support)
+/// confirmed within the last `lookback` bars, in %. Bars without such a level have no value.
+pub fn distance_to_level(prices: &Series, n: usize, lookback: usize, resistance: bool) -> Series {
+    let swings = swings(prices, n, resistance);
+    prices
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (date, bar))| {
+            let close = bar.0;
+            let confirmed = swings
+                .iter()
+                .filter(|(idx, _)| idx + n <= i && idx + lookback >= i)
+                .map(|(_, level)| *level);
+            let value = if resistance {
+                let nearest = confirmed.filter(|level| *level > close).fold(f64::MAX, f64::min);
+                (nearest < f64::MAX && close > 0.0).then(|| (nearest / close - 1.0) * 100.0)
+            } else {
+                let nearest = confirmed.filter(|level| *level < close).fold(f64::MIN, f64::max);
+                (nearest > 0.0).then(|| (close / nearest - 1.0) * 100.0)
+            };
+            value.map(|value| (date.clone(), (value, 0.0, 0.0, 0.0)))
+        })
+        .collect()
+}
+
+
+/// `PIVOTS(fibonacci, 1w)`: pivot points of the previous week.
+pub struct Pivots;
+
+impl Indicator for Pivots {
+    fn name(&self) -> &'static str {
+        "PIVOTS"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[
+            ParamSpec::choice("type", PIVOT_TYPES, "classic"),
+            ParamSpec::duration("period", "1d"),
+        ];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["pp", "r1", "r2", "r3", "s1", "s2", "s3"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let timeframe = match Timeframe::from_duration(params.duration("period")) {
+            Some(timeframe) => timeframe,
+            None => panic!("PIVOTS: period must be 1d, 1w, 1m or 1q, got {}", params.duration("period")),
+        };
+        pivots(input.prices, params.choice("type"), timeframe)
+    }
+}
+
+pub struct SwingHigh;
+
+impl Indicator for SwingHigh {
+    fn name(&self) -> &'static str {
+        "SWINGHIGH"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("n", 5)];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![swing_level(input.prices, params.length("n"), true)]
+    }
+}
+
+pub struct SwingLow;
+
+impl Indicator for SwingLow {
+    fn name(&self) -> &'static str {
+        "SWINGLOW"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("n", 5)];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![swing_level(input.prices, params.length("n"), false)]
+    }
+}
+
+pub struct DistToResistance;
+
+impl Indicator for DistToResistance {
+    fn name(&self) -> &'static str {
+        "DIST_TO_RESISTANCE"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("n", 5), ParamSpec::length("lookback", 252)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![distance_to_level(input.prices, params.length("n"), params.length("lookback"), true)]
+    }
+}
+
+pub struct DistToSupport;
+
+impl Indicator for DistToSupport {
+    fn name(&self) -> &'static str {
+        "DIST_TO_SUPPORT"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("n", 5), ParamSpec::length("lookback", 252)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![distance_to_level(input.prices, params.length("n"), params.length("lookback"), false)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::test_data::assert_close;
+
+    // bars of only a close, one per day from 2024-01-01
+    fn closes(values: &[f64]) -> Series {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, close)| (format!("2024-01-{:02}", i + 1), (*close, *close, *close, *close)))
+            .collect()
+    }
+
+    fn assert_levels(actual: [f64; 7], expected: [f64; 7]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?}, expected {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn pivot_levels_of_each_type() {
+        assert_levels(pivot_levels("classic", 12.0, 8.0, 10.0), [10.0, 12.0, 14.0, 16.0, 8.0, 6.0, 4.0]);
+        assert_levels(pivot_levels("fibonacci", 12.0, 8.0, 10.0), [10.0, 11.528, 12.472, 14.0, 8.472, 7.528, 6.0]);
+        assert_levels(
+            pivot_levels("camarilla", 12.0, 8.0, 10.0),
+            [10.0, 10.0 + 4.4 / 12.0, 10.0 + 4.4 / 6.0, 11.1, 10.0 - 4.4 / 12.0, 10.0 - 4.4 / 6.0, 8.9],
+        );
+    }
+
+    #[test]
+    fn pivots_use_the_previous_period() {
+        let prices: Series = vec![
+            ("2024-01-01".to_string(), (10.0, 9.0, 12.0, 8.0)),
+            ("2024-01-02".to_string(), (11.0, 10.0, 11.5, 9.5)),
+            ("2024-01-03".to_string(), (10.5, 11.0, 11.0, 10.0)),
+        ];
+        let levels = pivots(&prices, "classic", Timeframe::Daily);
+        assert_eq!(levels.len(), 7);
+        assert_eq!(levels[0].len(), 2);
+        assert_close(&levels[0][0], "2024-01-02", 10.0);
+        assert_close(&levels[3][0], "2024-01-02", 16.0);
+        assert_close(&levels[0][1], "2024-01-03", 32.0 / 3.0);
+    }
+
+    #[test]
+    fn swings_need_n_bars_on_both_sides() {
+        assert_eq!(swings(&closes(&[1.0, 2.0, 3.0, 2.0, 1.0, 2.0, 4.0, 3.0]), 2, true), vec![(2, 3.0)]);
+        assert_eq!(swings(&closes(&[3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0]), 2, false), vec![(2, 1.0)]);
+    }
+
+    #[test]
+    fn first_of_equal_swings_counts() {
+        assert_eq!(swings(&closes(&[1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 0.0]), 2, true), vec![(2, 3.0)]);
+    }
+
+    #[test]
+    fn swing_level_from_the_confirming_bar() {
+        let levels = swing_level(&closes(&[10.0, 14.0, 15.0, 14.0, 10.0, 11.0, 12.0, 11.0, 10.0, 9.0]), 2, true);
+        assert_eq!(levels.len(), 6);
+        assert_close(&levels[0], "2024-01-05", 15.0);
+        assert_close(&levels[3], "2024-01-08", 15.0);
+        assert_close(&levels[4], "2024-01-09", 12.0);
+    }
+
+    #[test]
+    fn distance_to_the_nearest_resistance_above() {
+        let distance = distance_to_level(&closes(&[10.0, 14.0, 15.0, 14.0, 10.0, 11.0, 12.0, 11.0, 10.0, 9.0]), 2, 252, true);
+        assert_eq!(distance.len(), 6);
+        assert_close(&distance[0], "2024-01-05", 50.0);
+        assert_close(&distance[1], "2024-01-06", (15.0 / 11.0 - 1.0) * 100.0);
+        // the swing at 12 is confirmed and nearer than 15
+        assert_close(&distance[4], "2024-01-09", 20.0);
+        assert_close(&distance[5], "2024-01-10", (12.0 / 9.0 - 1.0) * 100.0);
+    }
+
+    #[test]
+    fn no_distance_above_every_resistance() {
+        let distance = distance_to_level(&closes(&[10.0, 11.0, 12.0, 11.0, 10.0, 9.0, 10.0, 13.0, 14.0]), 2, 252, true);
+        assert_eq!(distance.len(), 3);
+        assert_close(&distance[2], "2024-01-07", 20.0);
+    }
+
+    #[test]
+    fn distance_to_support_within_the_lookback() {
+        let prices = closes(&[10.0, 9.0, 8.0, 9.0, 10.0, 11.0, 12.0]);
+        let distance = distance_to_level(&prices, 2, 252, false);
+        assert_eq!(distance.len(), 3);
+        assert_close(&distance[0], "2024-01-05", 25.0);
+        assert_close(&distance[2], "2024-01-07", 50.0);
+
+        // the swing low is more than 3 bars old on the last bar
+        let distance = distance_to_level(&prices, 2, 3, false);
+        assert_eq!(distance.len(), 2);
+        assert_close(&distance[1], "2024-01-06", 37.5);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, ParamSpec, Params, Series};
+
 pub fn sma(prices: &Vec<(String, (f64, f64, f64, f64))>, len: u64) -> Vec<(String, (f64, f64, f64, f64))> {
     let len = len as usize;
     if prices.len() < len {
@@ -64,4 +66,55 @@ pub fn wma(prices: &Vec<(String, (f64, f64, f64, f64))>, len: u64) -> Vec<(Strin
     }
 
     result
-}
\ No newline at end of file
+}
+
+pub struct Sma;
+
+impl Indicator for Sma {
+    fn name(&self) -> &'static str {
+        "MA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![sma(input.prices, params.length("period") as u64)]
+    }
+}
+
+pub struct Ema;
+
+impl Indicator for Ema {
+    fn name(&self) -> &'static str {
+        "EMA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![ema(input.prices, params.length("period") as u64)]
+    }
+}
+
+pub struct Wma;
+
+impl Indicator for Wma {
+    fn name(&self) -> &'static str {
+        "WMA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![wma(input.prices, params.length("period") as u64)]
+    }
+}
@@ -0,0 +1,65 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::ma::ema;
+use crate::response_types::ChartType;
+
+// values of two series over the dates they share, both end on the last bar
+fn zip_tail(a: &Series, b: &Series, f: impl Fn(f64, f64) -> f64) -> Series {
+    let len = a.len().min(b.len());
+    a[a.len() - len..]
+        .iter()
+        .zip(&b[b.len() - len..])
+        .map(|((date, x), (_, y))| (date.clone(), (f(x.0, y.0), 0.0, 0.0, 0.0)))
+        .collect()
+}
+
+/// MACD line (fast EMA - slow EMA), its signal line (EMA of the MACD line) and the
+/// histogram (MACD - signal).
+pub fn macd(prices: &Series, fast: usize, slow: usize, signal: usize) -> (Series, Series, Series) {
+    let macd_line = zip_tail(&ema(prices, fast as u64), &ema(prices, slow as u64), |f, s| f - s);
+    let signal_line = ema(&macd_line, signal as u64);
+    let hist = zip_tail(&macd_line, &signal_line, |m, s| m - s);
+    (macd_line, signal_line, hist)
+}
+
+
+pub struct Macd;
+
+impl Indicator for Macd {
+    fn name(&self) -> &'static str {
+        "MACD"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[
+            ParamSpec::length("fast", 12),
+            ParamSpec::length("slow", 26),
+            ParamSpec::length("signal", 9),
+        ];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["macd", "signal", "hist"]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn chart_type(&self, output: &str) -> ChartType {
+        match output {
+            "hist" => ChartType::Histogram,
+            _ => ChartType::Indicator,
+        }
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (macd_line, signal_line, hist) = macd(
+            input.prices,
+            params.length("fast"),
+            params.length("slow"),
+            params.length("signal"),
+        );
+        vec![macd_line, signal_line, hist]
+    }
+}
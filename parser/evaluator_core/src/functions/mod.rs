@@ -1,4 +1,21 @@
 pub mod functions;
+pub mod indicator;
+pub mod registry;
 pub mod ma;
 pub mod rsi;
-pub mod bb;
\ No newline at end of file
+pub mod bb;
+pub mod cross_section;
+pub mod levels;
+pub mod macd;
+pub mod momentum;
+pub mod patterns;
+pub mod relative;
+pub mod stats;
+pub mod stoch;
+pub mod transform;
+pub mod trend;
+pub mod volatility;
+pub mod volume;
+
+#[cfg(test)]
+mod test_data;
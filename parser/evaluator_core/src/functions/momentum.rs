@@ -0,0 +1,271 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::ma::ema;
+use crate::functions::volatility::bar_range;
+
+/// Rate of change, % change of the close over `period` bars.
+pub fn roc(prices: &Series, period: usize) -> Series {
+    if period == 0 || prices.len() <= period {
+        return vec![];
+    }
+
+    (period..prices.len())
+        .map(|i| {
+            let prev = prices[i - period].1.0;
+            let value = if prev != 0.0 { (prices[i].1.0 - prev) / prev * 100.0 } else { 0.0 };
+            (prices[i].0.clone(), (value, 0.0, 0.0, 0.0))
+        })
+        .collect()
+}
+
+/// Momentum, change of the close over `period` bars.
+pub fn mom(prices: &Series, period: usize) -> Series {
+    if period == 0 || prices.len() <= period {
+        return vec![];
+    }
+
+    (period..prices.len())
+        .map(|i| (prices[i].0.clone(), (prices[i].1.0 - prices[i - period].1.0, 0.0, 0.0, 0.0)))
+        .collect()
+}
+
+/// Commodity Channel Index, distance of the typical price from its SMA in units of 0.015 mean deviations.
+pub fn cci(prices: &Series, period: usize) -> Series {
+    if period == 0 || prices.len() < period {
+        return vec![];
+    }
+
+    let typical: Vec<f64> = prices
+        .iter()
+        .map(|(_, bar)| {
+            let (high, low) = bar_range(bar);
+            (high + low + bar.0) / 3.0
+        })
+        .collect();
+
+    let mut result: Series = Vec::with_capacity(prices.len() - period + 1);
+    for i in (period - 1)..prices.len() {
+        let window = &typical[i + 1 - period..=i];
+        let mean = window.iter().sum::<f64>() / period as f64;
+        let deviation = window.iter().map(|tp| (tp - mean).abs()).sum::<f64>() / period as f64;
+        let value = if deviation > 0.0 { (typical[i] - mean) / (0.015 * deviation) } else { 0.0 };
+        result.push((prices[i].0.clone(), (value, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// Williams %R, where the close is in the range of the last `period` bars, from -100 (low) to 0 (high).
+/// Windows without a range read -50.
+pub fn willr(prices: &Series, period: usize) -> Series {
+    if period == 0 || prices.len() < period {
+        return vec![];
+    }
+
+    let mut result: Series = Vec::with_capacity(prices.len() - period + 1);
+    for i in (period - 1)..prices.len() {
+        let window = &prices[i + 1 - period..=i];
+        let high = window.iter().map(|(_, v)| bar_range(v).0).fold(f64::MIN, f64::max);
+        let low = window.iter().map(|(_, v)| bar_range(v).1).fold(f64::MAX, f64::min);
+        let value = if high > low { -100.0 * (high - prices[i].1.0) / (high - low) } else { -50.0 };
+        result.push((prices[i].0.clone(), (value, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// True Strength Index, close changes smoothed by an EMA over `long` and then `short` bars,
+/// relative to the same smoothing of their absolute values.
+pub fn tsi(prices: &Series, long: usize, short: usize) -> Series {
+    if prices.len() < 2 {
+        return vec![];
+    }
+
+    let (changes, abs_changes): (Series, Series) = prices
+        .windows(2)
+        .map(|w| {
+            let change = w[1].1.0 - w[0].1.0;
+            (
+                (w[1].0.clone(), (change, 0.0, 0.0, 0.0)),
+                (w[1].0.clone(), (change.abs(), 0.0, 0.0, 0.0)),
+            )
+        })
+        .unzip();
+    let smoothed = ema(&ema(&changes, long as u64), short as u64);
+    let smoothed_abs = ema(&ema(&abs_changes, long as u64), short as u64);
+
+    smoothed
+        .into_iter()
+        .zip(smoothed_abs)
+        .map(|((date, v), (_, a))| {
+            let value = if a.0 > 0.0 { 100.0 * v.0 / a.0 } else { 0.0 };
+            (date, (value, 0.0, 0.0, 0.0))
+        })
+        .collect()
+}
+
+
+pub struct Roc;
+
+impl Indicator for Roc {
+    fn name(&self) -> &'static str {
+        "ROC"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 12)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![roc(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Mom;
+
+impl Indicator for Mom {
+    fn name(&self) -> &'static str {
+        "MOM"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 10)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![mom(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Cci;
+
+impl Indicator for Cci {
+    fn name(&self) -> &'static str {
+        "CCI"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![cci(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Willr;
+
+impl Indicator for Willr {
+    fn name(&self) -> &'static str {
+        "WILLR"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(-100.0, 0.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![willr(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Tsi;
+
+impl Indicator for Tsi {
+    fn name(&self) -> &'static str {
+        "TSI"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("long", 25), ParamSpec::length("short", 13)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(-100.0, 100.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![tsi(input.prices, params.length("long"), params.length("short"))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::test_data::{adpl, assert_close};
+
+    #[test]
+    fn roc_matches_reference() {
+        let roc = roc(&adpl(), 5);
+        assert_eq!(roc.len(), 19);
+        assert_close(&roc[0], "2015-01-13", -2.8471263146711916);
+        assert_close(&roc[1], "2015-01-14", -1.92943183029528);
+        assert_close(&roc[18], "2015-02-09", -5.458515283842795);
+    }
+
+    #[test]
+    fn mom_matches_reference() {
+        let mom = mom(&adpl(), 5);
+        assert_eq!(mom.len(), 19);
+        assert_close(&mom[0], "2015-01-13", -2.6799999999999926);
+        assert_close(&mom[1], "2015-01-14", -1.8100000000000023);
+        assert_close(&mom[18], "2015-02-09", -4.5);
+    }
+
+    #[test]
+    fn cci_matches_reference() {
+        let cci = cci(&adpl(), 5);
+        assert_eq!(cci.len(), 20);
+        assert_close(&cci[0], "2015-01-12", -166.6666666666626);
+        assert_close(&cci[1], "2015-01-13", -105.42352224253428);
+        assert_close(&cci[19], "2015-02-09", -155.26509364567443);
+    }
+
+    #[test]
+    fn willr_matches_reference() {
+        let willr = willr(&adpl(), 5);
+        assert_eq!(willr.len(), 20);
+        assert_close(&willr[0], "2015-01-12", -68.59296482412063);
+        assert_close(&willr[1], "2015-01-13", -89.19597989949733);
+        assert_close(&willr[19], "2015-02-09", -87.53351206434326);
+    }
+
+    #[test]
+    fn tsi_matches_reference() {
+        let tsi = tsi(&adpl(), 5, 3);
+        assert_eq!(tsi.len(), 17);
+        assert_close(&tsi[0], "2015-01-15", -51.547358549546715);
+        assert_close(&tsi[1], "2015-01-16", -80.08901382056692);
+        assert_close(&tsi[16], "2015-02-09", -82.43050937514779);
+    }
+
+    #[test]
+    fn short_history_is_empty() {
+        let prices = adpl()[..5].to_vec();
+        assert!(roc(&prices, 5).is_empty());
+        assert!(mom(&prices, 5).is_empty());
+        assert!(cci(&prices, 6).is_empty());
+        assert!(willr(&prices, 6).is_empty());
+        assert!(tsi(&prices, 5, 3).is_empty());
+    }
+}
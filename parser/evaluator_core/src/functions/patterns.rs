@@ -0,0 +1,305 @@
+use std::collections::HashSet;
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::volatility::bar_range;
+use crate::response_types::ChartType;
+
+// open, high, low and close of one bar
+#[derive(Debug, Clone, Copy)]
+struct Candle {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+}
+
+impl Candle {
+    // None for bars without an open, their body is unknown
+    fn from_bar(bar: &(f64, f64, f64, f64)) -> Option<Self> {
+        let (high, low) = bar_range(bar);
+        (bar.1 > 0.0).then(|| Candle {
+            open: bar.1,
+            high: high.max(bar.1).max(bar.0),
+            low: low.min(bar.1).min(bar.0),
+            close: bar.0,
+        })
+    }
+
+    fn body(&self) -> f64 {
+        (self.close - self.open).abs()
+    }
+
+    fn range(&self) -> f64 {
+        self.high - self.low
+    }
+
+    fn upper_shadow(&self) -> f64 {
+        self.high - self.open.max(self.close)
+    }
+
+    fn lower_shadow(&self) -> f64 {
+        self.open.min(self.close) - self.low
+    }
+
+    fn bullish(&self) -> bool {
+        self.close > self.open
+    }
+
+    fn bearish(&self) -> bool {
+        self.close < self.open
+    }
+
+    // middle of the body
+    fn midpoint(&self) -> f64 {
+        (self.open + self.close) / 2.0
+    }
+}
+
+fn doji(c: &[Candle]) -> bool {
+    let bar = c[0];
+    bar.range() > 0.0 && bar.body() <= 0.1 * bar.range()
+}
+
+// long lower shadow after the close fell over the previous bars
+fn hammer(c: &[Candle]) -> bool {
+    let bar = c[5];
+    bar.body() > 0.0
+        && bar.lower_shadow() >= 2.0 * bar.body()
+        && bar.upper_shadow() <= bar.body()
+        && c[4].close < c[0].close
+}
+
+fn engulfing_bull(c: &[Candle]) -> bool {
+    let (prev, bar) = (c[0], c[1]);
+    prev.bearish() && bar.bullish() && bar.open <= prev.close && bar.close >= prev.open && bar.body() > prev.body()
+}
+
+fn engulfing_bear(c: &[Candle]) -> bool {
+    let (prev, bar) = (c[0], c[1]);
+    prev.bullish() && bar.bearish() && bar.open >= prev.close && bar.close <= prev.open && bar.body() > prev.body()
+}
+
+// long down bar, a small body, then an up bar closing above the middle of the first one
+fn morning_star(c: &[Candle]) -> bool {
+    let (first, star, last) = (c[0], c[1], c[2]);
+    first.bearish()
+        && first.body() >= 0.5 * first.range()
+        && star.body() <= 0.3 * first.body()
+        && last.bullish()
+        && last.close > first.midpoint()
+}
+
+fn evening_star(c: &[Candle]) -> bool {
+    let (first, star, last) = (c[0], c[1], c[2]);
+    first.bullish()
+        && first.body() >= 0.5 * first.range()
+        && star.body() <= 0.3 * first.body()
+        && last.bearish()
+        && last.close < first.midpoint()
+}
+
+fn inside_bar(c: &[Candle]) -> bool {
+    c[1].high < c[0].high && c[1].low > c[0].low
+}
+
+fn gap_up(c: &[Candle]) -> bool {
+    c[1].low > c[0].high
+}
+
+fn gap_down(c: &[Candle]) -> bool {
+    c[1].high < c[0].low
+}
+
+/// A candlestick pattern, 1 on the bars that complete it and 0 on the others. Charted as markers
+/// on the price panel, the side of the marker is carried as the second value of each point.
+#[derive(Clone, Copy)]
+pub struct Pattern {
+    name: &'static str,
+    // bars the pattern looks at, ending with the one it fires on
+    bars: usize,
+    // 1 bullish (marker below the bar), -1 bearish (above the bar), 0 neither
+    side: f64,
+    matches: fn(&[Candle]) -> bool,
+}
+
+pub const PATTERNS: &[Pattern] = &[
+    Pattern { name: "DOJI", bars: 1, side: 0.0, matches: doji },
+    Pattern { name: "HAMMER", bars: 6, side: 1.0, matches: hammer },
+    Pattern { name: "ENGULFING_BULL", bars: 2, side: 1.0, matches: engulfing_bull },
+    Pattern { name: "ENGULFING_BEAR", bars: 2, side: -1.0, matches: engulfing_bear },
+    Pattern { name: "MORNING_STAR", bars: 3, side: 1.0, matches: morning_star },
+    Pattern { name: "EVENING_STAR", bars: 3, side: -1.0, matches: evening_star },
+    Pattern { name: "INSIDE_BAR", bars: 2, side: 0.0, matches: inside_bar },
+    Pattern { name: "GAP_UP", bars: 2, side: 1.0, matches: gap_up },
+    Pattern { name: "GAP_DOWN", bars: 2, side: -1.0, matches: gap_down },
+];
+
+impl Pattern {
+    /// 1 or 0 for every bar from the first one the pattern can complete on.
+    pub fn detect(&self, prices: &Series) -> Series {
+        if prices.len() < self.bars {
+            return vec![];
+        }
+
+        let candles: Vec<Option<Candle>> = prices.iter().map(|(_, bar)| Candle::from_bar(bar)).collect();
+        (self.bars - 1..prices.len())
+            .map(|i| {
+                let window: Option<Vec<Candle>> = candles[i + 1 - self.bars..=i].iter().copied().collect();
+                let fired = window.is_some_and(|window| (self.matches)(&window));
+                (prices[i].0.clone(), (if fired { 1.0 } else { 0.0 }, self.side, 0.0, 0.0))
+            })
+            .collect()
+    }
+}
+
+impl Indicator for Pattern {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Price
+    }
+
+    fn chart_type(&self, _output: &str) -> ChartType {
+        ChartType::Marker
+    }
+
+    // the bars tell which days have no open, the price series fills it with the close
+    fn uses_volume(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, _params: &Params) -> Vec<Series> {
+        let no_open: HashSet<&str> = input
+            .bars
+            .iter()
+            .filter(|bar| bar.open_price.is_none())
+            .map(|bar| bar.date.as_str())
+            .collect();
+        let prices: Series = input
+            .prices
+            .iter()
+            .map(|(date, bar)| match no_open.contains(date.as_str()) {
+                true => (date.clone(), (bar.0, 0.0, bar.2, bar.3)),
+                false => (date.clone(), *bar),
+            })
+            .collect();
+        vec![self.detect(&prices)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::indicator::resolve_params;
+    use crate::types::DailyPrice;
+
+    fn pattern(name: &str) -> Pattern {
+        *PATTERNS.iter().find(|p| p.name == name).unwrap()
+    }
+
+    // hand-built candles as (open, high, low, close), one per day from 2024-01-01
+    fn candles(bars: &[(f64, f64, f64, f64)]) -> Series {
+        bars.iter()
+            .enumerate()
+            .map(|(i, (open, high, low, close))| (format!("2024-01-{:02}", i + 1), (*close, *open, *high, *low)))
+            .collect()
+    }
+
+    // 1 or 0 on the last bar
+    fn fires(name: &str, bars: &[(f64, f64, f64, f64)]) -> bool {
+        pattern(name).detect(&candles(bars)).last().unwrap().1.0 == 1.0
+    }
+
+    #[test]
+    fn doji_has_a_small_body() {
+        assert!(fires("DOJI", &[(10.05, 11.0, 9.0, 10.0)]));
+        assert!(!fires("DOJI", &[(9.0, 11.0, 9.0, 10.0)]));
+        // no range, no candle to judge
+        assert!(!fires("DOJI", &[(10.0, 10.0, 10.0, 10.0)]));
+    }
+
+    #[test]
+    fn hammer_after_a_decline() {
+        let decline = [(12.2, 12.3, 11.9, 12.0), (12.0, 12.1, 11.4, 11.5), (11.5, 11.6, 10.9, 11.0), (11.0, 11.1, 10.4, 10.5), (10.5, 10.6, 10.1, 10.2)];
+        let hammer = (10.0, 10.3, 9.4, 10.2);
+        assert!(fires("HAMMER", &[decline.as_slice(), &[hammer]].concat()));
+
+        let rise: Vec<_> = decline.iter().rev().copied().collect();
+        assert!(!fires("HAMMER", &[rise.as_slice(), &[hammer]].concat()));
+    }
+
+    #[test]
+    fn engulfing_bars() {
+        assert!(fires("ENGULFING_BULL", &[(10.0, 10.1, 8.9, 9.0), (8.9, 10.3, 8.8, 10.2)]));
+        assert!(!fires("ENGULFING_BEAR", &[(10.0, 10.1, 8.9, 9.0), (8.9, 10.3, 8.8, 10.2)]));
+        assert!(fires("ENGULFING_BEAR", &[(9.0, 10.1, 8.9, 10.0), (10.1, 10.2, 8.7, 8.8)]));
+        // the second body does not cover the first one
+        assert!(!fires("ENGULFING_BULL", &[(10.0, 10.1, 8.9, 9.0), (9.2, 10.0, 9.1, 9.9)]));
+    }
+
+    #[test]
+    fn stars() {
+        let first = (12.0, 12.2, 9.9, 10.0);
+        let star = (9.8, 10.0, 9.6, 9.9);
+        assert!(fires("MORNING_STAR", &[first, star, (10.0, 11.6, 9.9, 11.5)]));
+        // the last bar closes below the middle of the first one
+        assert!(!fires("MORNING_STAR", &[first, star, (10.0, 10.9, 9.9, 10.8)]));
+        assert!(fires("EVENING_STAR", &[(10.0, 12.1, 9.8, 12.0), (12.1, 12.4, 12.0, 12.2), (12.0, 12.1, 10.4, 10.5)]));
+    }
+
+    #[test]
+    fn inside_bars_and_gaps() {
+        assert!(fires("INSIDE_BAR", &[(10.0, 11.0, 9.0, 10.5), (10.2, 10.8, 9.5, 10.4)]));
+        assert!(!fires("INSIDE_BAR", &[(10.0, 11.0, 9.0, 10.5), (10.2, 11.2, 9.5, 10.4)]));
+        assert!(fires("GAP_UP", &[(9.5, 10.0, 9.0, 9.8), (10.6, 11.0, 10.5, 10.9)]));
+        assert!(fires("GAP_DOWN", &[(9.5, 10.0, 9.0, 9.8), (8.6, 8.9, 8.5, 8.7)]));
+        assert!(!fires("GAP_UP", &[(9.5, 10.0, 9.0, 9.8), (9.9, 11.0, 9.9, 10.9)]));
+    }
+
+    #[test]
+    fn every_bar_after_the_window_gets_a_value() {
+        let series = pattern("ENGULFING_BULL").detect(&candles(&[(10.0, 10.1, 8.9, 9.0), (8.9, 10.3, 8.8, 10.2), (10.2, 10.4, 10.0, 10.3)]));
+        let values: Vec<(f64, f64)> = series.iter().map(|(_, v)| (v.0, v.1)).collect();
+        assert_eq!(values, vec![(1.0, 1.0), (0.0, 1.0)]);
+        assert_eq!(series[0].0, "2024-01-02");
+        assert!(pattern("MORNING_STAR").detect(&candles(&[(10.0, 10.1, 8.9, 9.0)])).is_empty());
+    }
+
+    #[test]
+    fn bars_without_an_open_are_skipped() {
+        let bar = |open: Option<f64>| DailyPrice {
+            id: 0,
+            stock_isin: "SI0031102120".to_string(),
+            date: "2024-01-01".to_string(),
+            trading_model_id: None,
+            open_price: open,
+            high_price: Some(11.0),
+            low_price: Some(9.0),
+            last_price: Some(10.0),
+            vwap_price: None,
+            change_prev_close_percentage: None,
+            num_trades: None,
+            volume: None,
+            turnover: None,
+            price_currency: None,
+            turnover_currency: None,
+        };
+        // the price series fills the missing open with the close, a zero body
+        let prices = candles(&[(10.0, 11.0, 9.0, 10.0)]);
+        let doji = pattern("DOJI");
+        let compute = |bars: &[DailyPrice]| {
+            let input = IndicatorInput { prices: &prices, actions: &[], bars, benchmark: &vec![] };
+            doji.compute(&input, &resolve_params(&doji, &[]).unwrap())[0][0].1.0
+        };
+
+        assert_eq!(compute(&[bar(None)]), 0.0);
+        assert_eq!(compute(&[bar(Some(10.0))]), 1.0);
+        // indexes and derived items have no bars, their series keep the open
+        assert_eq!(compute(&[]), 1.0);
+    }
+}
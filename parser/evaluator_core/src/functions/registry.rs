@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::functions::bb::{Bb, Bbwp, BbwpMa};
+use crate::functions::indicator::Indicator;
+use crate::functions::macd::Macd;
+use crate::functions::momentum::{Cci, Mom, Roc, Tsi, Willr};
+use crate::functions::ma::{Ema, Sma, Wma};
+use crate::functions::levels::{DistToResistance, DistToSupport, Pivots, SwingHigh, SwingLow};
+use crate::functions::patterns::PATTERNS;
+use crate::functions::relative::{Alpha, Beta, Corr, Rs, TrackingError};
+use crate::functions::rsi::{Rsi, RsiMa};
+use crate::functions::stats::{CumRet, Drawdown, Highest, Lowest, MaxDd, Percentile, Ret, Stdev, Sum, Zscore};
+use crate::functions::stoch::Stoch;
+use crate::functions::transform::{HeikinAshi, LineBreak, Renko, Resample, TotalReturn};
+use crate::functions::trend::{Adx, Aroon, Psar, Supertrend};
+use crate::functions::volatility::{Atr, Donchian, Hv, Kc, Natr};
+use crate::functions::volume::{Adt, Cmf, Mfi, Obv, Rvol, Vwap};
+
+/// Indicators callable from scripts, by name.
+#[derive(Default)]
+pub struct Registry {
+    indicators: HashMap<&'static str, Box<dyn Indicator>>,
+}
+
+impl Registry {
+    /// Adds an indicator, replacing one registered under the same name.
+    pub fn register(&mut self, indicator: Box<dyn Indicator>) {
+        self.indicators.insert(indicator.name(), indicator);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Indicator> {
+        self.indicators.get(name).map(|i| i.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.indicators.keys().copied().collect();
+        names.sort();
+        names
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::default();
+        registry.register(Box::new(Rsi));
+        registry.register(Box::new(RsiMa));
+        registry.register(Box::new(Sma));
+        registry.register(Box::new(Ema));
+        registry.register(Box::new(Wma));
+        registry.register(Box::new(Bbwp));
+        registry.register(Box::new(BbwpMa));
+        registry.register(Box::new(Stoch));
+        registry.register(Box::new(Macd));
+        registry.register(Box::new(Bb));
+        registry.register(Box::new(Atr));
+        registry.register(Box::new(Natr));
+        registry.register(Box::new(Hv));
+        registry.register(Box::new(Kc));
+        registry.register(Box::new(Donchian));
+        registry.register(Box::new(Adx));
+        registry.register(Box::new(Aroon));
+        registry.register(Box::new(Supertrend));
+        registry.register(Box::new(Psar));
+        registry.register(Box::new(Obv));
+        registry.register(Box::new(Mfi));
+        registry.register(Box::new(Cmf));
+        registry.register(Box::new(Vwap));
+        registry.register(Box::new(Rvol));
+        registry.register(Box::new(Adt));
+        registry.register(Box::new(Roc));
+        registry.register(Box::new(Mom));
+        registry.register(Box::new(Cci));
+        registry.register(Box::new(Willr));
+        registry.register(Box::new(Tsi));
+        registry.register(Box::new(Ret));
+        registry.register(Box::new(CumRet));
+        registry.register(Box::new(Drawdown));
+        registry.register(Box::new(MaxDd));
+        registry.register(Box::new(Stdev));
+        registry.register(Box::new(Highest));
+        registry.register(Box::new(Lowest));
+        registry.register(Box::new(Zscore));
+        registry.register(Box::new(Percentile));
+        registry.register(Box::new(Sum));
+        registry.register(Box::new(Rs));
+        registry.register(Box::new(Beta));
+        registry.register(Box::new(Corr));
+        registry.register(Box::new(Alpha));
+        registry.register(Box::new(TrackingError));
+        registry.register(Box::new(Pivots));
+        registry.register(Box::new(SwingHigh));
+        registry.register(Box::new(SwingLow));
+        registry.register(Box::new(DistToResistance));
+        registry.register(Box::new(DistToSupport));
+        for pattern in PATTERNS {
+            registry.register(Box::new(*pattern));
+        }
+        registry.register(Box::new(TotalReturn));
+        registry.register(Box::new(Resample));
+        registry.register(Box::new(HeikinAshi));
+        registry.register(Box::new(Renko));
+        registry.register(Box::new(LineBreak));
+        registry
+    }
+}
+
+pub fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::with_builtins)
+}
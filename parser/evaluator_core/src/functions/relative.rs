@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+
+/// Index an item is compared with when the script doesn't name a benchmark, by the item's market.
+pub fn home_benchmark(mic: &str) -> Option<&'static str> {
+    match mic {
+        "XLJU" => Some("SBITOP"),
+        "XZAG" => Some("CBX"),
+        _ => None,
+    }
+}
+
+// (date, item close, benchmark close) of the days both have a price
+fn aligned(prices: &Series, benchmark: &Series) -> Vec<(String, f64, f64)> {
+    let benchmark: HashMap<&str, f64> = benchmark.iter().map(|(date, v)| (date.as_str(), v.0)).collect();
+    prices
+        .iter()
+        .filter_map(|(date, v)| benchmark.get(date.as_str()).map(|b| (date.clone(), v.0, *b)))
+        .collect()
+}
+
+// (date, item return, benchmark return) of every aligned day from the second one on
+fn aligned_returns(prices: &Series, benchmark: &Series) -> Vec<(String, f64, f64)> {
+    aligned(prices, benchmark)
+        .windows(2)
+        .map(|w| {
+            let change = |prev: f64, close: f64| if prev > 0.0 { close / prev - 1.0 } else { 0.0 };
+            (w[1].0.clone(), change(w[0].1, w[1].1), change(w[0].2, w[1].2))
+        })
+        .collect()
+}
+
+// value of every window of `period` returns, dated at the last day of the window
+fn rolling_returns(prices: &Series, benchmark: &Series, period: usize, f: impl Fn(&[f64], &[f64]) -> f64) -> Series {
+    let returns = aligned_returns(prices, benchmark);
+    if period < 2 || returns.len() < period {
+        return vec![];
+    }
+
+    let (item, bench): (Vec<f64>, Vec<f64>) = returns.iter().map(|(_, r, b)| (*r, *b)).unzip();
+    (period - 1..returns.len())
+        .map(|i| {
+            let window = i + 1 - period..=i;
+            (returns[i].0.clone(), (f(&item[window.clone()], &bench[window]), 0.0, 0.0, 0.0))
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// sample covariance
+fn covariance(a: &[f64], b: &[f64]) -> f64 {
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    a.iter().zip(b).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum::<f64>() / (a.len() - 1) as f64
+}
+
+fn beta_of(item: &[f64], bench: &[f64]) -> f64 {
+    let variance = covariance(bench, bench);
+    if variance > 0.0 { covariance(item, bench) / variance } else { 0.0 }
+}
+
+/// Mansfield relative strength, the item / benchmark ratio in % above or below its `period` day average.
+pub fn rs(prices: &Series, benchmark: &Series, period: usize) -> Series {
+    let ratios: Vec<(String, f64)> = aligned(prices, benchmark)
+        .into_iter()
+        .filter(|(_, _, b)| *b > 0.0)
+        .map(|(date, close, b)| (date, close / b))
+        .collect();
+    if period == 0 || ratios.len() < period {
+        return vec![];
+    }
+
+    let mut sum: f64 = ratios[..period - 1].iter().map(|(_, r)| r).sum();
+    let mut result: Series = Vec::with_capacity(ratios.len() - period + 1);
+    for i in (period - 1)..ratios.len() {
+        sum += ratios[i].1;
+        let average = sum / period as f64;
+        let value = if average > 0.0 { (ratios[i].1 / average - 1.0) * 100.0 } else { 0.0 };
+        result.push((ratios[i].0.clone(), (value, 0.0, 0.0, 0.0)));
+        sum -= ratios[i + 1 - period].1;
+    }
+
+    result
+}
+
+/// Beta of the daily returns against the benchmark over the last `period` days.
+pub fn beta(prices: &Series, benchmark: &Series, period: usize) -> Series {
+    rolling_returns(prices, benchmark, period, beta_of)
+}
+
+/// Correlation of the daily returns with the benchmark over the last `period` days, from -1 to 1.
+pub fn corr(prices: &Series, benchmark: &Series, period: usize) -> Series {
+    rolling_returns(prices, benchmark, period, |item, bench| {
+        let deviation = (covariance(item, item) * covariance(bench, bench)).sqrt();
+        if deviation > 0.0 { covariance(item, bench) / deviation } else { 0.0 }
+    })
+}
+
+/// Jensen's alpha without a risk-free rate, the annualized % return not explained by beta.
+pub fn alpha(prices: &Series, benchmark: &Series, period: usize) -> Series {
+    rolling_returns(prices, benchmark, period, |item, bench| {
+        (mean(item) - beta_of(item, bench) * mean(bench)) * 252.0 * 100.0
+    })
+}
+
+/// Tracking error, the annualized % standard deviation of the return difference to the benchmark.
+pub fn tracking_error(prices: &Series, benchmark: &Series, period: usize) -> Series {
+    rolling_returns(prices, benchmark, period, |item, bench| {
+        let diffs: Vec<f64> = item.iter().zip(bench).map(|(r, b)| r - b).collect();
+        covariance(&diffs, &diffs).sqrt() * 252.0_f64.sqrt() * 100.0
+    })
+}
+
+pub struct Rs;
+
+impl Indicator for Rs {
+    fn name(&self) -> &'static str {
+        "RS"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn uses_benchmark(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![rs(input.prices, input.benchmark, params.length("period"))]
+    }
+}
+
+pub struct Beta;
+
+impl Indicator for Beta {
+    fn name(&self) -> &'static str {
+        "BETA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252).at_least(2)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn uses_benchmark(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![beta(input.prices, input.benchmark, params.length("period"))]
+    }
+}
+
+pub struct Corr;
+
+impl Indicator for Corr {
+    fn name(&self) -> &'static str {
+        "CORR"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252).at_least(2)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(-1.0, 1.0)
+    }
+
+    fn uses_benchmark(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![corr(input.prices, input.benchmark, params.length("period"))]
+    }
+}
+
+pub struct Alpha;
+
+impl Indicator for Alpha {
+    fn name(&self) -> &'static str {
+        "ALPHA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252).at_least(2)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn uses_benchmark(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![alpha(input.prices, input.benchmark, params.length("period"))]
+    }
+}
+
+pub struct TrackingError;
+
+impl Indicator for TrackingError {
+    fn name(&self) -> &'static str {
+        "TRACKING_ERROR"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252).at_least(2)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn uses_benchmark(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![tracking_error(input.prices, input.benchmark, params.length("period"))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::indicator::resolve_params;
+    use crate::functions::test_data::{adpl, assert_close, cbx};
+    use parser_core::ast::FunctionArg;
+
+    #[test]
+    fn rs_matches_reference() {
+        let rs = rs(&adpl(), &cbx(), 5);
+        assert_eq!(rs.len(), 20);
+        assert_close(&rs[0], "2015-01-12", -1.7955171523668878);
+        assert_close(&rs[1], "2015-01-13", -2.0957126435082407);
+        assert_close(&rs[19], "2015-02-09", -4.674133844192053);
+    }
+
+    #[test]
+    fn beta_matches_reference() {
+        let beta = beta(&adpl(), &cbx(), 5);
+        assert_eq!(beta.len(), 19);
+        assert_close(&beta[0], "2015-01-13", 0.9347700552213505);
+        assert_close(&beta[1], "2015-01-14", 1.9754745333696826);
+        assert_close(&beta[18], "2015-02-09", 2.9504153201608183);
+    }
+
+    #[test]
+    fn corr_matches_reference() {
+        let corr = corr(&adpl(), &cbx(), 5);
+        assert_eq!(corr.len(), 19);
+        assert_close(&corr[0], "2015-01-13", 0.6796596242414641);
+        assert_close(&corr[1], "2015-01-14", 0.8984635555794207);
+        assert_close(&corr[18], "2015-02-09", 0.4520916105102178);
+    }
+
+    #[test]
+    fn alpha_matches_reference() {
+        let alpha = alpha(&adpl(), &cbx(), 5);
+        assert_eq!(alpha.len(), 19);
+        assert_close(&alpha[0], "2015-01-13", -243.71662782101805);
+        assert_close(&alpha[1], "2015-01-14", -225.26884873534385);
+        assert_close(&alpha[18], "2015-02-09", -300.65567130426774);
+    }
+
+    #[test]
+    fn tracking_error_matches_reference() {
+        let te = tracking_error(&adpl(), &cbx(), 5);
+        assert_eq!(te.len(), 19);
+        assert_close(&te[0], "2015-01-13", 11.80536999774025);
+        assert_close(&te[1], "2015-01-14", 11.340591873009938);
+        assert_close(&te[18], "2015-02-09", 30.761324861092383);
+    }
+
+    #[test]
+    fn return_windows_need_two_days() {
+        let err = resolve_params(&Beta, &[FunctionArg::Number(1.0)]).err().unwrap();
+        assert!(err.contains("at least 2"), "{}", err);
+        assert!(resolve_params(&Corr, &[FunctionArg::Number(2.0)]).is_ok());
+        // 24 aligned closes give 23 returns
+        assert_eq!(beta(&adpl(), &cbx(), 23).len(), 1);
+        assert!(beta(&adpl(), &cbx(), 24).is_empty());
+    }
+}
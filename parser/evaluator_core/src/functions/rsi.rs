@@ -1,3 +1,6 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::ma::sma;
+
 
 pub fn rsi(prices: &Vec<(String, (f64, f64, f64, f64))>, len: u64) -> Vec<(String, (f64, f64, f64, f64))> {
     let len = len as usize;
@@ -51,4 +54,48 @@ pub fn rsi(prices: &Vec<(String, (f64, f64, f64, f64))>, len: u64) -> Vec<(Strin
     }
 
     rsis
-}
\ No newline at end of file
+}
+
+pub struct Rsi;
+
+impl Indicator for Rsi {
+    fn name(&self) -> &'static str {
+        "RSI"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![rsi(input.prices, params.length("period") as u64)]
+    }
+}
+
+// moving average of the RSI
+pub struct RsiMa;
+
+impl Indicator for RsiMa {
+    fn name(&self) -> &'static str {
+        "RSIMA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14), ParamSpec::length("ma_period", 14)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let rsi = rsi(input.prices, params.length("period") as u64);
+        vec![sma(&rsi, params.length("ma_period") as u64)]
+    }
+}
@@ -0,0 +1,375 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+
+// value of every window of `period` closes, dated at the last bar of the window
+fn rolling(prices: &Series, period: usize, f: impl Fn(&[f64]) -> f64) -> Series {
+    if period == 0 || prices.len() < period {
+        return vec![];
+    }
+
+    let closes: Vec<f64> = prices.iter().map(|(_, v)| v.0).collect();
+    (period - 1..prices.len())
+        .map(|i| (prices[i].0.clone(), (f(&closes[i + 1 - period..=i]), 0.0, 0.0, 0.0)))
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// sample standard deviation, 0 for a single value
+fn stdev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(values);
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+// drawdown of the last value from the highest one before it, in %
+fn drawdowns(values: &[f64]) -> impl Iterator<Item = f64> + '_ {
+    values.iter().scan(f64::MIN, |peak, v| {
+        *peak = peak.max(*v);
+        Some(if *peak > 0.0 { (v / *peak - 1.0) * 100.0 } else { 0.0 })
+    })
+}
+
+/// Simple and log returns over `period` bars, in %.
+pub fn returns(prices: &Series, period: usize) -> (Series, Series) {
+    if period == 0 || prices.len() <= period {
+        return (vec![], vec![]);
+    }
+
+    (period..prices.len())
+        .map(|i| {
+            let (prev, close) = (prices[i - period].1.0, prices[i].1.0);
+            let (simple, log) = if prev > 0.0 && close > 0.0 {
+                ((close / prev - 1.0) * 100.0, (close / prev).ln() * 100.0)
+            } else {
+                (0.0, 0.0)
+            };
+            let date = &prices[i].0;
+            ((date.clone(), (simple, 0.0, 0.0, 0.0)), (date.clone(), (log, 0.0, 0.0, 0.0)))
+        })
+        .unzip()
+}
+
+/// Return since the first bar of the date range, in %.
+pub fn cumulative_return(prices: &Series) -> Series {
+    let Some(first) = prices.first().map(|(_, v)| v.0) else {
+        return vec![];
+    };
+
+    prices
+        .iter()
+        .map(|(date, v)| {
+            let value = if first > 0.0 { (v.0 / first - 1.0) * 100.0 } else { 0.0 };
+            (date.clone(), (value, 0.0, 0.0, 0.0))
+        })
+        .collect()
+}
+
+/// Distance of the close from its running peak since the first bar of the date range, in % (0 or negative).
+pub fn drawdown(prices: &Series) -> Series {
+    let closes: Vec<f64> = prices.iter().map(|(_, v)| v.0).collect();
+    prices
+        .iter()
+        .zip(drawdowns(&closes))
+        .map(|((date, _), dd)| (date.clone(), (dd, 0.0, 0.0, 0.0)))
+        .collect()
+}
+
+/// Deepest drawdown within each window of `period` bars, in % (0 or negative).
+pub fn max_drawdown(prices: &Series, period: usize) -> Series {
+    rolling(prices, period, |window| drawdowns(window).fold(0.0, f64::min))
+}
+
+/// Distance of the close from the mean of the last `period` closes, in sample standard deviations.
+pub fn zscore(prices: &Series, period: usize) -> Series {
+    rolling(prices, period, |window| {
+        let sd = stdev(window);
+        if sd > 0.0 { (window[window.len() - 1] - mean(window)) / sd } else { 0.0 }
+    })
+}
+
+/// Share of the last `period` closes at or below the current one, in %.
+pub fn percentile(prices: &Series, period: usize) -> Series {
+    rolling(prices, period, |window| {
+        let current = window[window.len() - 1];
+        window.iter().filter(|v| **v <= current).count() as f64 / window.len() as f64 * 100.0
+    })
+}
+
+/// Sample standard deviation of the last `period` closes.
+pub fn rolling_stdev(prices: &Series, period: usize) -> Series {
+    rolling(prices, period, stdev)
+}
+
+/// Highest close of the last `period` bars.
+pub fn highest(prices: &Series, period: usize) -> Series {
+    rolling(prices, period, |window| window.iter().cloned().fold(f64::MIN, f64::max))
+}
+
+/// Lowest close of the last `period` bars.
+pub fn lowest(prices: &Series, period: usize) -> Series {
+    rolling(prices, period, |window| window.iter().cloned().fold(f64::MAX, f64::min))
+}
+
+/// Sum of the last `period` values.
+pub fn rolling_sum(prices: &Series, period: usize) -> Series {
+    rolling(prices, period, |window| window.iter().sum())
+}
+
+pub struct Ret;
+
+impl Indicator for Ret {
+    fn name(&self) -> &'static str {
+        "RET"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 1)];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["simple", "log"]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn charted_outputs(&self) -> &'static [&'static str] {
+        &["simple"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (simple, log) = returns(input.prices, params.length("period"));
+        vec![simple, log]
+    }
+}
+
+pub struct CumRet;
+
+impl Indicator for CumRet {
+    fn name(&self) -> &'static str {
+        "CUMRET"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, _params: &Params) -> Vec<Series> {
+        vec![cumulative_return(input.prices)]
+    }
+}
+
+pub struct Drawdown;
+
+impl Indicator for Drawdown {
+    fn name(&self) -> &'static str {
+        "DRAWDOWN"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, _params: &Params) -> Vec<Series> {
+        vec![drawdown(input.prices)]
+    }
+}
+
+pub struct MaxDd;
+
+impl Indicator for MaxDd {
+    fn name(&self) -> &'static str {
+        "MAXDD"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![max_drawdown(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Stdev;
+
+impl Indicator for Stdev {
+    fn name(&self) -> &'static str {
+        "STDEV"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![rolling_stdev(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Highest;
+
+impl Indicator for Highest {
+    fn name(&self) -> &'static str {
+        "HIGHEST"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252)];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![highest(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Lowest;
+
+impl Indicator for Lowest {
+    fn name(&self) -> &'static str {
+        "LOWEST"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252)];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![lowest(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Zscore;
+
+impl Indicator for Zscore {
+    fn name(&self) -> &'static str {
+        "ZSCORE"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![zscore(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Percentile;
+
+impl Indicator for Percentile {
+    fn name(&self) -> &'static str {
+        "PERCENTILE"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 252)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![percentile(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Sum;
+
+impl Indicator for Sum {
+    fn name(&self) -> &'static str {
+        "SUM"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![rolling_sum(input.prices, params.length("period"))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::test_data::{adpl, assert_close};
+
+    #[test]
+    fn drawdown_matches_reference() {
+        let drawdown = drawdown(&adpl());
+        assert_eq!(drawdown.len(), 24);
+        assert_close(&drawdown[0], "2015-01-02", 0.0);
+        // below the 94.31 close of 01-08
+        assert_close(&drawdown[4], "2015-01-12", -2.163079206870966);
+        assert_close(&drawdown[8], "2015-01-16", -8.578093521365716);
+        assert_close(&drawdown[23], "2015-02-09", -17.357650302194894);
+    }
+
+    #[test]
+    fn max_drawdown_matches_reference() {
+        let maxdd = max_drawdown(&adpl(), 5);
+        assert_eq!(maxdd.len(), 20);
+        assert_close(&maxdd[0], "2015-01-12", -2.163079206870966);
+        assert_close(&maxdd[1], "2015-01-13", -3.032552221397522);
+        assert_close(&maxdd[19], "2015-02-09", -6.669859897018327);
+    }
+
+    #[test]
+    fn percentile_matches_reference() {
+        let percentile = percentile(&adpl(), 5);
+        assert_eq!(percentile.len(), 20);
+        assert_close(&percentile[0], "2015-01-12", 20.0);
+        assert_close(&percentile[2], "2015-01-14", 40.0);
+        assert_close(&percentile[7], "2015-01-21", 80.0);
+        assert_close(&percentile[19], "2015-02-09", 20.0);
+    }
+
+    #[test]
+    fn percentile_counts_equal_closes() {
+        let prices: Series = [1.0, 2.0, 2.0]
+            .iter()
+            .enumerate()
+            .map(|(i, close)| (format!("2024-01-{:02}", i + 1), (*close, 0.0, 0.0, 0.0)))
+            .collect();
+        assert_close(&percentile(&prices, 3)[0], "2024-01-03", 100.0);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::ma::sma;
+use crate::functions::volatility::bar_range;
+
+/// Stochastic oscillator, returns (%K, %D). The raw %K of the last `k` bars is smoothed
+/// with an SMA over `smooth` bars and %D is the SMA of %K over `d` bars.
+/// Windows without a range (high == low, common on thin books) read 50.
+pub fn stoch(
+    prices: &Series,
+    k: usize,
+    d: usize,
+    smooth: usize,
+) -> (Series, Series) {
+    if k == 0 || prices.len() < k {
+        return (vec![], vec![]);
+    }
+
+    let mut raw: Vec<(String, (f64, f64, f64, f64))> = Vec::with_capacity(prices.len() - k + 1);
+    for i in (k - 1)..prices.len() {
+        let window = &prices[i + 1 - k..=i];
+        let high = window.iter().map(|(_, v)| bar_range(v).0).fold(f64::MIN, f64::max);
+        let low = window.iter().map(|(_, v)| bar_range(v).1).fold(f64::MAX, f64::min);
+        let close = prices[i].1.0;
+        let value = if high > low { (close - low) / (high - low) * 100.0 } else { 50.0 };
+        raw.push((prices[i].0.clone(), (value, 0.0, 0.0, 0.0)));
+    }
+
+    let k_line = sma(&raw, smooth as u64);
+    let d_line = sma(&k_line, d as u64);
+    (k_line, d_line)
+}
+
+pub struct Stoch;
+
+impl Indicator for Stoch {
+    fn name(&self) -> &'static str {
+        "STOCH"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[
+            ParamSpec::length("k", 14),
+            ParamSpec::length("d", 3),
+            ParamSpec::length("smooth", 3),
+        ];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["k", "d"]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (k_line, d_line) = stoch(input.prices, params.length("k"), params.length("d"), params.length("smooth"));
+        vec![k_line, d_line]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::test_data::{adpl, assert_close};
+
+    #[test]
+    fn stoch_matches_reference() {
+        let (k, d) = stoch(&adpl(), 5, 3, 3);
+
+        assert_eq!(k.len(), 18);
+        assert_close(&k[0], "2015-01-14", 22.319510007952278);
+        assert_close(&k[1], "2015-01-15", 27.03674470099729);
+        assert_close(&k[2], "2015-01-16", 28.31150378819983);
+        assert_close(&k[17], "2015-02-09", 21.99529747260627);
+
+        assert_eq!(d.len(), 16);
+        assert_close(&d[0], "2015-01-16", 25.889252832383132);
+        assert_close(&d[1], "2015-01-19", 29.329457268672154);
+        assert_close(&d[2], "2015-01-20", 30.757390223389013);
+        assert_close(&d[15], "2015-02-09", 25.75139359193396);
+    }
+
+    #[test]
+    fn stoch_flat_window_reads_midpoint() {
+        let flat: Vec<(String, (f64, f64, f64, f64))> = (1..=5)
+            .map(|i| (format!("2024-01-0{}", i), (10.0, 10.0, 10.0, 10.0)))
+            .collect();
+        let (k, d) = stoch(&flat, 3, 1, 1);
+        assert_eq!(k.len(), 3);
+        assert!(k.iter().all(|(_, v)| v.0 == 50.0));
+        assert_eq!(d.len(), 3);
+    }
+
+    #[test]
+    fn stoch_short_history_is_empty() {
+        let (k, d) = stoch(&adpl()[..4].to_vec(), 5, 3, 3);
+        assert!(k.is_empty());
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn bar_without_a_range_uses_its_close() {
+        // the middle bar has no high and low, it must not pull the low of the window to 0
+        let prices: Series = vec![
+            ("2024-01-01".to_string(), (10.0, 0.0, 11.0, 9.0)),
+            ("2024-01-02".to_string(), (12.0, 0.0, 0.0, 0.0)),
+            ("2024-01-03".to_string(), (11.0, 0.0, 12.0, 10.0)),
+        ];
+        let (k, _) = stoch(&prices, 3, 1, 1);
+        assert_eq!(k.len(), 1);
+        assert_close(&k[0], "2024-01-03", (11.0 - 9.0) / (12.0 - 9.0) * 100.0);
+    }
+}
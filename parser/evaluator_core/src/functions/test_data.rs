@@ -0,0 +1,74 @@
+// price histories shared by the indicator tests
+
+// first ADPL sessions of 2015 from scripts/data_stocks, (close, open, high, low)
+pub fn adpl() -> Vec<(String, (f64, f64, f64, f64))> {
+    [
+        ("2015-01-02", (94.13, 92.99, 95.0, 92.99)),
+        ("2015-01-05", (93.81, 95.0, 95.0, 93.35)),
+        ("2015-01-07", (94.02, 94.5, 94.5, 94.0)),
+        ("2015-01-08", (94.31, 94.0, 94.98, 92.02)),
+        ("2015-01-12", (92.27, 93.34, 93.34, 91.02)),
+        ("2015-01-13", (91.45, 91.9, 91.98, 91.17)),
+        ("2015-01-14", (92.0, 91.99, 92.1, 91.99)),
+        ("2015-01-15", (91.18, 92.5, 92.5, 88.0)),
+        ("2015-01-16", (86.22, 87.91, 87.91, 85.0)),
+        ("2015-01-19", (87.83, 88.49, 88.97, 87.2)),
+        ("2015-01-20", (88.12, 88.99, 89.5, 86.03)),
+        ("2015-01-21", (88.51, 88.9, 88.98, 87.1)),
+        ("2015-01-22", (87.1, 87.11, 87.11, 87.1)),
+        ("2015-01-23", (87.14, 87.14, 87.14, 87.14)),
+        ("2015-01-26", (87.68, 87.66, 87.86, 87.65)),
+        ("2015-01-27", (87.3, 87.7, 87.7, 86.1)),
+        ("2015-01-29", (86.17, 86.99, 86.99, 85.52)),
+        ("2015-01-30", (84.9, 85.28, 85.97, 84.0)),
+        ("2015-02-02", (82.44, 84.09, 84.09, 82.2)),
+        ("2015-02-03", (83.48, 83.47, 83.5, 83.47)),
+        ("2015-02-04", (83.51, 84.34, 84.47, 82.0)),
+        ("2015-02-05", (83.13, 84.0, 84.0, 82.0)),
+        ("2015-02-06", (81.12, 83.5, 84.0, 80.0)),
+        ("2015-02-09", (77.94, 80.0, 80.0, 77.01)),
+    ]
+    .iter()
+    .map(|(d, v)| (d.to_string(), *v))
+    .collect()
+}
+
+// CROBEX closes of the same period from scripts/data_indexes, with two days ADPL didn't trade
+pub fn cbx() -> Vec<(String, (f64, f64, f64, f64))> {
+    [
+        ("2015-01-02", (1740.6, 1745.39, 1748.08, 1740.16)),
+        ("2015-01-05", (1765.12, 1740.61, 1766.7, 1740.11)),
+        ("2015-01-07", (1770.68, 1765.12, 1773.58, 1762.06)),
+        ("2015-01-08", (1781.74, 1770.75, 1785.74, 1767.7)),
+        ("2015-01-09", (1772.13, 1781.83, 1782.56, 1772.06)),
+        ("2015-01-12", (1770.25, 1772.38, 1773.06, 1767.62)),
+        ("2015-01-13", (1777.52, 1770.18, 1778.62, 1767.29)),
+        ("2015-01-14", (1787.94, 1777.52, 1788.0, 1772.06)),
+        ("2015-01-15", (1772.92, 1788.07, 1789.8, 1769.61)),
+        ("2015-01-16", (1779.88, 1772.88, 1781.17, 1769.24)),
+        ("2015-01-19", (1790.89, 1780.21, 1793.0, 1779.94)),
+        ("2015-01-20", (1796.7, 1790.91, 1800.85, 1789.38)),
+        ("2015-01-21", (1807.9, 1796.55, 1807.9, 1784.66)),
+        ("2015-01-22", (1790.76, 1808.25, 1808.25, 1789.08)),
+        ("2015-01-23", (1780.67, 1790.74, 1790.74, 1778.68)),
+        ("2015-01-26", (1778.9, 1780.69, 1780.69, 1774.44)),
+        ("2015-01-27", (1771.6, 1779.09, 1781.69, 1770.82)),
+        ("2015-01-28", (1771.89, 1771.43, 1779.86, 1765.9)),
+        ("2015-01-29", (1764.11, 1772.09, 1772.31, 1760.22)),
+        ("2015-01-30", (1764.75, 1763.47, 1766.81, 1761.32)),
+        ("2015-02-02", (1754.21, 1764.75, 1767.81, 1752.87)),
+        ("2015-02-03", (1757.79, 1754.68, 1757.79, 1751.99)),
+        ("2015-02-04", (1755.3, 1758.12, 1758.14, 1747.76)),
+        ("2015-02-05", (1758.3, 1753.87, 1762.68, 1752.57)),
+        ("2015-02-06", (1764.57, 1759.85, 1770.02, 1759.4)),
+        ("2015-02-09", (1756.97, 1765.35, 1766.44, 1756.68)),
+    ]
+    .iter()
+    .map(|(d, v)| (d.to_string(), *v))
+    .collect()
+}
+
+pub fn assert_close(actual: &(String, (f64, f64, f64, f64)), date: &str, value: f64) {
+    assert_eq!(actual.0, date);
+    assert!((actual.1.0 - value).abs() < 1e-9, "{} on {}, expected {}", actual.1.0, date, value);
+}
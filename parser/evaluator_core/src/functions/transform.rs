@@ -0,0 +1,288 @@
+use crate::adjust::total_return;
+use crate::functions::indicator::{Indicator, IndicatorInput, ParamSpec, Params, Series};
+use crate::functions::volatility::bar_range;
+use crate::resample::resample_series;
+use crate::response_types::ChartType;
+use crate::types::Timeframe;
+
+/// `TR(item)`: total return with dividends reinvested.
+pub struct TotalReturn;
+
+impl Indicator for TotalReturn {
+    fn name(&self) -> &'static str {
+        "TR"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    // actions are left out for already adjusted prices, so they aren't applied twice
+    fn uses_actions(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, _params: &Params) -> Vec<Series> {
+        vec![total_return(input.prices, input.actions)]
+    }
+}
+
+/// `RESAMPLE(item, 1w)`: the item's bars aggregated into a longer timeframe.
+pub struct Resample;
+
+impl Indicator for Resample {
+    fn name(&self) -> &'static str {
+        "RESAMPLE"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::duration("timeframe", "1w")];
+        PARAMS
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let timeframe = Timeframe::from_duration(params.duration("timeframe")).unwrap_or(Timeframe::Weekly);
+        vec![resample_series(input.prices, timeframe)]
+    }
+}
+
+/// Heikin-Ashi bars, the close averages the bar and the open the previous Heikin-Ashi bar's body.
+pub fn heikin_ashi(prices: &Series) -> Series {
+    let mut result: Series = Vec::with_capacity(prices.len());
+    let mut prev: Option<(f64, f64)> = None;
+    for (date, bar) in prices {
+        let (high, low) = bar_range(bar);
+        // bars without an open start at the close
+        let open = if bar.1 > 0.0 { bar.1 } else { bar.0 };
+        let ha_close = (open + high + low + bar.0) / 4.0;
+        let ha_open = match prev {
+            Some((prev_open, prev_close)) => (prev_open + prev_close) / 2.0,
+            None => (open + bar.0) / 2.0,
+        };
+        let ha_high = high.max(ha_open).max(ha_close);
+        let ha_low = low.min(ha_open).min(ha_close);
+        result.push((date.clone(), (ha_close, ha_open, ha_high, ha_low)));
+        prev = Some((ha_open, ha_close));
+    }
+
+    result
+}
+
+/// Renko bricks of `brick` price units built from closes, a reversal needs a move of two bricks.
+/// Bricks completed on the same day are merged into one bar, so every bar has its own date.
+pub fn renko(prices: &Series, brick: f64) -> Series {
+    let Some(first) = prices.first() else {
+        return vec![];
+    };
+
+    // top and bottom of the last brick
+    let (mut top, mut bottom) = (first.1.0, first.1.0);
+    let mut result: Series = Vec::new();
+    for (date, bar) in &prices[1..] {
+        let close = bar.0;
+        if close >= top + brick {
+            let bricks = ((close - top) / brick).floor();
+            let (open, end) = (top, top + bricks * brick);
+            result.push((date.clone(), (end, open, end, open)));
+            (top, bottom) = (end, end - brick);
+        } else if close <= bottom - brick {
+            let bricks = ((bottom - close) / brick).floor();
+            let (open, end) = (bottom, bottom - bricks * brick);
+            result.push((date.clone(), (end, open, open, end)));
+            (top, bottom) = (end + brick, end);
+        }
+    }
+
+    result
+}
+
+/// Line break bars, a new line when the close goes beyond the last line and a reversal
+/// when it goes beyond all of the last `lines` lines.
+pub fn line_break(prices: &Series, lines: usize) -> Series {
+    let Some(first) = prices.first() else {
+        return vec![];
+    };
+
+    // (open, close) of the lines drawn so far
+    let mut drawn: Vec<(f64, f64)> = Vec::new();
+    let mut result: Series = Vec::new();
+    for (date, bar) in &prices[1..] {
+        let close = bar.0;
+        let line = match drawn.last() {
+            None if close != first.1.0 => Some((first.1.0, close)),
+            None => None,
+            Some(&(open, last_close)) => {
+                let up = last_close > open;
+                let recent = &drawn[drawn.len().saturating_sub(lines)..];
+                let highest = recent.iter().map(|(o, c)| o.max(*c)).fold(f64::MIN, f64::max);
+                let lowest = recent.iter().map(|(o, c)| o.min(*c)).fold(f64::MAX, f64::min);
+                if (up && close > last_close) || (!up && close < last_close) {
+                    Some((last_close, close))
+                } else if (up && close < lowest) || (!up && close > highest) {
+                    // the reversal line starts where the last line started
+                    Some((open, close))
+                } else {
+                    None
+                }
+            }
+        };
+
+        if let Some((open, close)) = line {
+            drawn.push((open, close));
+            result.push((date.clone(), (close, open, open.max(close), open.min(close))));
+        }
+    }
+
+    result
+}
+
+/// `HA(item)`: Heikin-Ashi bars of the item.
+pub struct HeikinAshi;
+
+impl Indicator for HeikinAshi {
+    fn name(&self) -> &'static str {
+        "HA"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    fn chart_type(&self, _output: &str) -> ChartType {
+        ChartType::Price
+    }
+
+    fn compute(&self, input: &IndicatorInput, _params: &Params) -> Vec<Series> {
+        vec![heikin_ashi(input.prices)]
+    }
+}
+
+/// `RENKO(item, 2)`: Renko bricks of 2 price units.
+pub struct Renko;
+
+impl Indicator for Renko {
+    fn name(&self) -> &'static str {
+        "RENKO"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::number("brick", 1.0)];
+        PARAMS
+    }
+
+    fn chart_type(&self, _output: &str) -> ChartType {
+        ChartType::Price
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![renko(input.prices, params.number("brick"))]
+    }
+}
+
+/// `LINEBREAK(item, 3)`: three line break bars.
+pub struct LineBreak;
+
+impl Indicator for LineBreak {
+    fn name(&self) -> &'static str {
+        "LINEBREAK"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("lines", 3)];
+        PARAMS
+    }
+
+    fn chart_type(&self, _output: &str) -> ChartType {
+        ChartType::Price
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![line_break(input.prices, params.length("lines"))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one bar per day from 2024-01-01, only the close matters for renko and line break
+    fn closes(values: &[f64]) -> Series {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, close)| (format!("2024-01-{:02}", i + 1), (*close, *close, close + 0.5, close - 0.5)))
+            .collect()
+    }
+
+    fn assert_bar(actual: &(String, (f64, f64, f64, f64)), date: &str, bar: (f64, f64, f64, f64)) {
+        assert_eq!(actual.0, date);
+        let (a, b) = (actual.1, bar);
+        assert!(
+            (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9 && (a.2 - b.2).abs() < 1e-9 && (a.3 - b.3).abs() < 1e-9,
+            "{:?} on {}, expected {:?}",
+            a,
+            date,
+            b
+        );
+    }
+
+    #[test]
+    fn heikin_ashi_bars() {
+        let prices: Series = vec![
+            ("2024-01-01".to_string(), (11.0, 10.0, 12.0, 9.0)),
+            ("2024-01-02".to_string(), (12.5, 11.0, 13.0, 10.5)),
+            // no open, the bar starts at its close
+            ("2024-01-03".to_string(), (12.0, 0.0, 12.4, 11.6)),
+        ];
+        let ha = heikin_ashi(&prices);
+        assert_eq!(ha.len(), 3);
+        assert_bar(&ha[0], "2024-01-01", (10.5, 10.5, 12.0, 9.0));
+        assert_bar(&ha[1], "2024-01-02", (11.75, 10.5, 13.0, 10.5));
+        assert_bar(&ha[2], "2024-01-03", (12.0, 11.125, 12.4, 11.125));
+    }
+
+    #[test]
+    fn renko_merges_bricks_of_one_day() {
+        let bricks = renko(&closes(&[10.0, 10.5, 12.3, 12.6]), 1.0);
+        assert_eq!(bricks.len(), 1);
+        // two bricks, 10 to 12, on one bar
+        assert_bar(&bricks[0], "2024-01-03", (12.0, 10.0, 12.0, 10.0));
+    }
+
+    #[test]
+    fn renko_reverses_after_two_bricks() {
+        let bricks = renko(&closes(&[10.0, 12.3, 11.5, 10.9, 9.4, 13.2]), 1.0);
+        assert_eq!(bricks.len(), 3);
+        assert_bar(&bricks[0], "2024-01-02", (12.0, 10.0, 12.0, 10.0));
+        // one brick down from the top of the last brick is not enough, the reversal brick starts at its bottom
+        assert_bar(&bricks[1], "2024-01-05", (10.0, 11.0, 11.0, 10.0));
+        assert_bar(&bricks[2], "2024-01-06", (13.0, 11.0, 13.0, 11.0));
+    }
+
+    #[test]
+    fn renko_without_prices() {
+        assert!(renko(&vec![], 1.0).is_empty());
+        assert!(renko(&closes(&[10.0, 10.9, 9.1]), 1.0).is_empty());
+    }
+
+    #[test]
+    fn line_break_reverses_beyond_the_last_lines() {
+        let lines = line_break(&closes(&[10.0, 10.0, 11.0, 12.0, 11.5, 13.0, 10.5, 9.5, 12.5, 13.5]), 3);
+        assert_eq!(lines.len(), 5);
+        // no line until the close moves from the first one
+        assert_bar(&lines[0], "2024-01-03", (11.0, 10.0, 11.0, 10.0));
+        assert_bar(&lines[1], "2024-01-04", (12.0, 11.0, 12.0, 11.0));
+        assert_bar(&lines[2], "2024-01-06", (13.0, 12.0, 13.0, 12.0));
+        // below the low of the last three lines, starting at the open of the last one
+        assert_bar(&lines[3], "2024-01-08", (9.5, 12.0, 12.0, 9.5));
+        // 12.5 stays under the high of the last three lines, 13.5 reverses again
+        assert_bar(&lines[4], "2024-01-10", (13.5, 12.0, 13.5, 12.0));
+    }
+
+    #[test]
+    fn line_break_of_one_line_reverses_beyond_the_last_one() {
+        let lines = line_break(&closes(&[10.0, 11.0, 12.0, 10.5]), 1);
+        assert_eq!(lines.len(), 3);
+        assert_bar(&lines[2], "2024-01-04", (10.5, 11.0, 11.0, 10.5));
+    }
+}
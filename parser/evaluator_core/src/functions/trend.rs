@@ -0,0 +1,457 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::volatility::{atr, bar_range, true_ranges};
+
+// trend direction as a number usable in conditions, 1 up, -1 down, 0 undecided
+fn direction(up: bool, down: bool) -> f64 {
+    if up {
+        1.0
+    } else if down {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+/// ADX with the directional indicators, returns (ADX, +DI, -DI) using Wilder's smoothing.
+/// The DIs start after `period` bars, ADX after another `period` - 1 bars.
+pub fn adx(prices: &Series, period: usize) -> (Series, Series, Series) {
+    if period == 0 || prices.len() < 2 * period {
+        return (vec![], vec![], vec![]);
+    }
+
+    // directional movement of every bar from the second one on, aligned with the true ranges
+    let (plus_dm, minus_dm): (Vec<f64>, Vec<f64>) = prices
+        .windows(2)
+        .map(|w| {
+            let (prev_high, prev_low) = bar_range(&w[0].1);
+            let (high, low) = bar_range(&w[1].1);
+            let up = high - prev_high;
+            let down = prev_low - low;
+            (
+                if up > down && up > 0.0 { up } else { 0.0 },
+                if down > up && down > 0.0 { down } else { 0.0 },
+            )
+        })
+        .unzip();
+    let tr = true_ranges(prices);
+
+    let mut tr_sum: f64 = tr[..period].iter().sum();
+    let mut plus_sum: f64 = plus_dm[..period].iter().sum();
+    let mut minus_sum: f64 = minus_dm[..period].iter().sum();
+
+    let mut plus_di: Series = Vec::with_capacity(tr.len() - period + 1);
+    let mut minus_di: Series = Vec::with_capacity(tr.len() - period + 1);
+    let mut adx: Series = Vec::with_capacity(tr.len() - 2 * period + 2);
+    let mut dx_values: Vec<f64> = Vec::with_capacity(period);
+    let mut adx_prev = 0.0;
+
+    for i in (period - 1)..tr.len() {
+        if i >= period {
+            tr_sum = tr_sum - tr_sum / period as f64 + tr[i];
+            plus_sum = plus_sum - plus_sum / period as f64 + plus_dm[i];
+            minus_sum = minus_sum - minus_sum / period as f64 + minus_dm[i];
+        }
+
+        let (pdi, mdi) = if tr_sum > 0.0 {
+            (100.0 * plus_sum / tr_sum, 100.0 * minus_sum / tr_sum)
+        } else {
+            (0.0, 0.0)
+        };
+        let dx = if pdi + mdi > 0.0 { 100.0 * (pdi - mdi).abs() / (pdi + mdi) } else { 0.0 };
+
+        // true range i belongs to bar i + 1
+        let date = &prices[i + 1].0;
+        plus_di.push((date.clone(), (pdi, 0.0, 0.0, 0.0)));
+        minus_di.push((date.clone(), (mdi, 0.0, 0.0, 0.0)));
+
+        if dx_values.len() < period {
+            dx_values.push(dx);
+            if dx_values.len() == period {
+                adx_prev = dx_values.iter().sum::<f64>() / period as f64;
+                adx.push((date.clone(), (adx_prev, 0.0, 0.0, 0.0)));
+            }
+        } else {
+            adx_prev = (adx_prev * (period - 1) as f64 + dx) / period as f64;
+            adx.push((date.clone(), (adx_prev, 0.0, 0.0, 0.0)));
+        }
+    }
+
+    (adx, plus_di, minus_di)
+}
+
+/// Aroon Up and Down, how recent the highest high and lowest low of the last `period` + 1 bars are (100 = today).
+pub fn aroon(prices: &Series, period: usize) -> (Series, Series) {
+    if period == 0 || prices.len() <= period {
+        return (vec![], vec![]);
+    }
+
+    let mut up: Series = Vec::with_capacity(prices.len() - period);
+    let mut down: Series = Vec::with_capacity(prices.len() - period);
+    for i in period..prices.len() {
+        let window = &prices[i - period..=i];
+        // the most recent extreme counts on ties
+        let mut high_idx = 0;
+        let mut low_idx = 0;
+        for (idx, (_, bar)) in window.iter().enumerate() {
+            let (high, low) = bar_range(bar);
+            if high >= bar_range(&window[high_idx].1).0 {
+                high_idx = idx;
+            }
+            if low <= bar_range(&window[low_idx].1).1 {
+                low_idx = idx;
+            }
+        }
+        let date = &prices[i].0;
+        up.push((date.clone(), (100.0 * high_idx as f64 / period as f64, 0.0, 0.0, 0.0)));
+        down.push((date.clone(), (100.0 * low_idx as f64 / period as f64, 0.0, 0.0, 0.0)));
+    }
+
+    (up, down)
+}
+
+/// Supertrend, returns (line, direction). The line follows the lower band (median price - `mult` ATRs)
+/// in an uptrend and the upper band in a downtrend, the trend flips when the close crosses it.
+pub fn supertrend(prices: &Series, period: usize, mult: f64) -> (Series, Series) {
+    let atr = atr(prices, period);
+    let bars = &prices[prices.len() - atr.len()..];
+
+    let mut line: Series = Vec::with_capacity(atr.len());
+    let mut dir: Series = Vec::with_capacity(atr.len());
+    let mut upper_prev = f64::MAX;
+    let mut lower_prev = f64::MIN;
+    let mut close_prev = 0.0;
+    let mut up = true;
+
+    for ((date, bar), (_, a)) in bars.iter().zip(&atr) {
+        let (high, low) = bar_range(bar);
+        let median = (high + low) / 2.0;
+        let close = bar.0;
+
+        // bands only move towards the price unless the previous close broke through them
+        let upper_basic = median + mult * a.0;
+        let lower_basic = median - mult * a.0;
+        let upper = if upper_basic < upper_prev || close_prev > upper_prev { upper_basic } else { upper_prev };
+        let lower = if lower_basic > lower_prev || close_prev < lower_prev { lower_basic } else { lower_prev };
+
+        if up && close < lower {
+            up = false;
+        } else if !up && close > upper {
+            up = true;
+        }
+
+        line.push((date.clone(), (if up { lower } else { upper }, 0.0, 0.0, 0.0)));
+        dir.push((date.clone(), (direction(up, !up), 0.0, 0.0, 0.0)));
+        upper_prev = upper;
+        lower_prev = lower;
+        close_prev = close;
+    }
+
+    (line, dir)
+}
+
+/// Parabolic SAR, returns (stop and reverse level, direction). The acceleration factor starts
+/// at `step`, grows by `step` with every new extreme and is capped at `max`.
+pub fn psar(prices: &Series, step: f64, max: f64) -> (Series, Series) {
+    if prices.len() < 2 {
+        return (vec![], vec![]);
+    }
+
+    let ranges: Vec<(f64, f64)> = prices.iter().map(|(_, bar)| bar_range(bar)).collect();
+    let mut up = prices[1].1.0 >= prices[0].1.0;
+    let mut sar = if up { ranges[0].1 } else { ranges[0].0 };
+    let mut extreme = if up { ranges[0].0 } else { ranges[0].1 };
+    let mut af = step;
+
+    let mut levels: Series = Vec::with_capacity(prices.len() - 1);
+    let mut dir: Series = Vec::with_capacity(prices.len() - 1);
+    for i in 1..prices.len() {
+        let (high, low) = ranges[i];
+        sar += af * (extreme - sar);
+
+        if up {
+            // the stop can't be above the lows of the last two bars
+            sar = sar.min(ranges[i - 1].1);
+            if i >= 2 {
+                sar = sar.min(ranges[i - 2].1);
+            }
+            if low < sar {
+                up = false;
+                sar = extreme;
+                extreme = low;
+                af = step;
+            } else if high > extreme {
+                extreme = high;
+                af = (af + step).min(max);
+            }
+        } else {
+            sar = sar.max(ranges[i - 1].0);
+            if i >= 2 {
+                sar = sar.max(ranges[i - 2].0);
+            }
+            if high > sar {
+                up = true;
+                sar = extreme;
+                extreme = high;
+                af = step;
+            } else if low < extreme {
+                extreme = low;
+                af = (af + step).min(max);
+            }
+        }
+
+        let date = &prices[i].0;
+        levels.push((date.clone(), (sar, 0.0, 0.0, 0.0)));
+        dir.push((date.clone(), (direction(up, !up), 0.0, 0.0, 0.0)));
+    }
+
+    (levels, dir)
+}
+
+pub struct Adx;
+
+impl Indicator for Adx {
+    fn name(&self) -> &'static str {
+        "ADX"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    // dir is 1 while +DI is above -DI and -1 while it is below
+    fn outputs(&self) -> &'static [&'static str] {
+        &["adx", "plus_di", "minus_di", "dir"]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn output_panel(&self, output: &str) -> Panel {
+        match output {
+            "dir" => Panel::Separate,
+            _ => self.panel(),
+        }
+    }
+
+    fn charted_outputs(&self) -> &'static [&'static str] {
+        &["adx", "plus_di", "minus_di"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (adx, plus_di, minus_di) = adx(input.prices, params.length("period"));
+        let dir = plus_di
+            .iter()
+            .zip(&minus_di)
+            .map(|((date, p), (_, m))| (date.clone(), (direction(p.0 > m.0, p.0 < m.0), 0.0, 0.0, 0.0)))
+            .collect();
+        vec![adx, plus_di, minus_di, dir]
+    }
+}
+
+pub struct Aroon;
+
+impl Indicator for Aroon {
+    fn name(&self) -> &'static str {
+        "AROON"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 25)];
+        PARAMS
+    }
+
+    // dir is 1 while Aroon Up is above Aroon Down and -1 while it is below
+    fn outputs(&self) -> &'static [&'static str] {
+        &["up", "down", "dir"]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn output_panel(&self, output: &str) -> Panel {
+        match output {
+            "dir" => Panel::Separate,
+            _ => self.panel(),
+        }
+    }
+
+    fn charted_outputs(&self) -> &'static [&'static str] {
+        &["up", "down"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (up, down) = aroon(input.prices, params.length("period"));
+        let dir = up
+            .iter()
+            .zip(&down)
+            .map(|((date, u), (_, d))| (date.clone(), (direction(u.0 > d.0, u.0 < d.0), 0.0, 0.0, 0.0)))
+            .collect();
+        vec![up, down, dir]
+    }
+}
+
+pub struct Supertrend;
+
+impl Indicator for Supertrend {
+    fn name(&self) -> &'static str {
+        "SUPERTREND"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 10), ParamSpec::number("mult", 3.0)];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["value", "dir"]
+    }
+
+    fn output_panel(&self, output: &str) -> Panel {
+        match output {
+            "dir" => Panel::Separate,
+            _ => Panel::Price,
+        }
+    }
+
+    fn charted_outputs(&self) -> &'static [&'static str] {
+        &["value"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (line, dir) = supertrend(input.prices, params.length("period"), params.number("mult"));
+        vec![line, dir]
+    }
+}
+
+pub struct Psar;
+
+impl Indicator for Psar {
+    fn name(&self) -> &'static str {
+        "PSAR"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::number("step", 0.02), ParamSpec::number("max", 0.2)];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["value", "dir"]
+    }
+
+    fn output_panel(&self, output: &str) -> Panel {
+        match output {
+            "dir" => Panel::Separate,
+            _ => Panel::Price,
+        }
+    }
+
+    fn charted_outputs(&self) -> &'static [&'static str] {
+        &["value"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (levels, dir) = psar(input.prices, params.number("step"), params.number("max"));
+        vec![levels, dir]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::indicator::resolve_params;
+    use crate::functions::test_data::{adpl, assert_close};
+    use parser_core::ast::FunctionArg;
+
+    #[test]
+    fn adx_matches_reference() {
+        let (adx, plus_di, minus_di) = adx(&adpl(), 5);
+        assert_eq!(plus_di.len(), 19);
+        assert_close(&plus_di[0], "2015-01-13", 0.0);
+        assert_close(&plus_di[1], "2015-01-14", 1.428231373482389);
+        assert_close(&plus_di[18], "2015-02-09", 1.098491888631341);
+        assert_close(&minus_di[0], "2015-01-13", 30.753353973168217);
+        assert_close(&minus_di[1], "2015-01-14", 28.374196619852448);
+        assert_close(&minus_di[18], "2015-02-09", 51.9018409017967);
+
+        // Wilder seeds ADX with the mean of the first `period` DX values
+        assert_eq!(adx.len(), 15);
+        assert_close(&adx[0], "2015-01-19", 90.89967765411093);
+        assert_close(&adx[1], "2015-01-20", 87.59792869880678);
+        assert_close(&adx[14], "2015-02-09", 85.29879544352909);
+    }
+
+    #[test]
+    fn supertrend_matches_reference() {
+        let (line, dir) = supertrend(&adpl(), 5, 3.0);
+        assert_eq!(line.len(), 19);
+        assert_close(&line[0], "2015-01-13", 85.761);
+        assert_close(&line[1], "2015-01-14", 87.00379999999998);
+        // the close of 01-16 breaks the lower band, the line jumps to the upper one
+        assert_close(&line[2], "2015-01-15", 87.00379999999998);
+        assert_close(&line[3], "2015-01-16", 95.549368);
+        assert_close(&line[18], "2015-02-09", 86.65323180598959);
+        assert_eq!(dir[2].1.0, 1.0);
+        assert!(dir[3..].iter().all(|(_, v)| v.0 == -1.0));
+    }
+
+    #[test]
+    fn psar_matches_reference() {
+        let (levels, dir) = psar(&adpl(), 0.02, 0.2);
+        assert_eq!(levels.len(), 23);
+        assert_close(&levels[0], "2015-01-05", 95.0);
+        assert_close(&levels[1], "2015-01-07", 95.0);
+        assert_close(&levels[22], "2015-02-09", 84.21523906068434);
+        assert!(dir.iter().all(|(_, v)| v.0 == -1.0));
+    }
+
+    // ten days up by one, then ten days down by one, each bar one above and below its close
+    fn up_then_down() -> Series {
+        (0..20)
+            .map(|i| {
+                let close = 100.0 + if i < 10 { i as f64 } else { 18.0 - i as f64 };
+                (format!("2024-01-{:02}", i + 1), (close, close, close + 1.0, close - 1.0))
+            })
+            .collect()
+    }
+
+    // dir output of the indicator with the given params, from the first value to the last
+    fn directions(indicator: &dyn Indicator, args: &[f64]) -> Vec<f64> {
+        let prices = up_then_down();
+        let input = IndicatorInput { prices: &prices, actions: &[], bars: &[], benchmark: &vec![] };
+        let args: Vec<FunctionArg> = args.iter().map(|n| FunctionArg::Number(*n)).collect();
+        let outputs = indicator.compute(&input, &resolve_params(indicator, &args).unwrap());
+        let dir = indicator.outputs().iter().position(|output| *output == "dir").unwrap();
+        outputs[dir].iter().map(|(_, v)| v.0).collect()
+    }
+
+    // the direction is up at first, turns down once and stays down
+    fn assert_flips_once(dir: &[f64]) {
+        let flip = dir.iter().position(|d| *d == -1.0).expect("no down direction");
+        assert!(flip > 0 && dir[..flip].iter().all(|d| *d == 1.0), "{:?}", dir);
+        assert!(dir[flip..].iter().all(|d| *d == -1.0), "{:?}", dir);
+    }
+
+    #[test]
+    fn adx_dir_flips() {
+        assert_flips_once(&directions(&Adx, &[3.0]));
+    }
+
+    #[test]
+    fn aroon_dir_flips() {
+        assert_flips_once(&directions(&Aroon, &[3.0]));
+    }
+
+    #[test]
+    fn supertrend_dir_flips() {
+        assert_flips_once(&directions(&Supertrend, &[3.0, 1.0]));
+    }
+
+    #[test]
+    fn psar_dir_flips() {
+        assert_flips_once(&directions(&Psar, &[]));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::ma::ema;
+
+// trading days used to annualize daily volatility
+const TRADING_DAYS: f64 = 252.0;
+
+// high and low of a bar, the close stands in for days without a recorded range
+pub fn bar_range(bar: &(f64, f64, f64, f64)) -> (f64, f64) {
+    let (close, _, high, low) = *bar;
+    if high > 0.0 && low > 0.0 { (high, low) } else { (close, close) }
+}
+
+/// True range of every bar from the second one on, it needs the previous close.
+pub fn true_ranges(prices: &Series) -> Vec<f64> {
+    prices
+        .windows(2)
+        .map(|w| {
+            let prev_close = w[0].1.0;
+            let (high, low) = bar_range(&w[1].1);
+            (high - low).max((high - prev_close).abs()).max((low - prev_close).abs())
+        })
+        .collect()
+}
+
+/// Average True Range with Wilder's smoothing, the first value is the mean of the first `period` true ranges.
+pub fn atr(prices: &Series, period: usize) -> Series {
+    if period == 0 || prices.len() <= period {
+        return vec![];
+    }
+
+    let true_ranges = true_ranges(prices);
+
+    let mut result: Series = Vec::with_capacity(true_ranges.len() - period + 1);
+    let mut atr = true_ranges[..period].iter().sum::<f64>() / period as f64;
+    result.push((prices[period].0.clone(), (atr, 0.0, 0.0, 0.0)));
+
+    for i in period..true_ranges.len() {
+        atr = (atr * (period - 1) as f64 + true_ranges[i]) / period as f64;
+        result.push((prices[i + 1].0.clone(), (atr, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// ATR in % of the close, comparable between items with different prices.
+pub fn natr(prices: &Series, period: usize) -> Series {
+    let atr = atr(prices, period);
+    let closes = &prices[prices.len() - atr.len()..];
+    atr.into_iter()
+        .zip(closes)
+        .map(|((date, v), (_, price))| {
+            let value = if price.0 != 0.0 { v.0 / price.0 * 100.0 } else { 0.0 };
+            (date, (value, 0.0, 0.0, 0.0))
+        })
+        .collect()
+}
+
+/// Historical volatility: sample standard deviation of daily log returns over `period` days,
+/// annualized and in %.
+pub fn hv(prices: &Series, period: usize) -> Series {
+    if period < 2 || prices.len() <= period {
+        return vec![];
+    }
+
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .map(|w| if w[0].1.0 > 0.0 && w[1].1.0 > 0.0 { (w[1].1.0 / w[0].1.0).ln() } else { 0.0 })
+        .collect();
+
+    let mut result: Series = Vec::with_capacity(returns.len() - period + 1);
+    for i in (period - 1)..returns.len() {
+        let window = &returns[i + 1 - period..=i];
+        let mean = window.iter().sum::<f64>() / period as f64;
+        let variance = window.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (period - 1) as f64;
+        result.push((prices[i + 1].0.clone(), (variance.sqrt() * TRADING_DAYS.sqrt() * 100.0, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// Keltner Channels (Middle, Upper, Lower), an EMA of the close with bands `mult` ATRs away.
+pub fn keltner_channels(prices: &Series, period: usize, mult: f64) -> (Series, Series, Series) {
+    let middle = ema(prices, period as u64);
+    let atr = atr(prices, period);
+    let len = middle.len().min(atr.len());
+
+    let middle = &middle[middle.len() - len..];
+    let atr = &atr[atr.len() - len..];
+    let band = |sign: f64| -> Series {
+        middle
+            .iter()
+            .zip(atr)
+            .map(|((date, m), (_, a))| (date.clone(), (m.0 + sign * mult * a.0, 0.0, 0.0, 0.0)))
+            .collect()
+    };
+    (middle.to_vec(), band(1.0), band(-1.0))
+}
+
+/// Donchian Channels (Middle, Upper, Lower), the highest high and lowest low of the last `period` bars.
+pub fn donchian_channels(prices: &Series, period: usize) -> (Series, Series, Series) {
+    if period == 0 || prices.len() < period {
+        return (vec![], vec![], vec![]);
+    }
+
+    let mut middle: Series = Vec::with_capacity(prices.len() - period + 1);
+    let mut upper: Series = Vec::with_capacity(prices.len() - period + 1);
+    let mut lower: Series = Vec::with_capacity(prices.len() - period + 1);
+    for i in (period - 1)..prices.len() {
+        let window = &prices[i + 1 - period..=i];
+        let high = window.iter().map(|(_, v)| bar_range(v).0).fold(f64::MIN, f64::max);
+        let low = window.iter().map(|(_, v)| bar_range(v).1).fold(f64::MAX, f64::min);
+        let date = &prices[i].0;
+        middle.push((date.clone(), ((high + low) / 2.0, 0.0, 0.0, 0.0)));
+        upper.push((date.clone(), (high, 0.0, 0.0, 0.0)));
+        lower.push((date.clone(), (low, 0.0, 0.0, 0.0)));
+    }
+
+    (middle, upper, lower)
+}
+
+pub struct Atr;
+
+impl Indicator for Atr {
+    fn name(&self) -> &'static str {
+        "ATR"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![atr(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Natr;
+
+impl Indicator for Natr {
+    fn name(&self) -> &'static str {
+        "NATR"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![natr(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Hv;
+
+impl Indicator for Hv {
+    fn name(&self) -> &'static str {
+        "HV"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20).at_least(2)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![hv(input.prices, params.length("period"))]
+    }
+}
+
+pub struct Kc;
+
+impl Indicator for Kc {
+    fn name(&self) -> &'static str {
+        "KC"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20), ParamSpec::number("mult", 2.0)];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["middle", "upper", "lower"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (middle, upper, lower) = keltner_channels(input.prices, params.length("period"), params.number("mult"));
+        vec![middle, upper, lower]
+    }
+}
+
+pub struct Donchian;
+
+impl Indicator for Donchian {
+    fn name(&self) -> &'static str {
+        "DONCHIAN"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["middle", "upper", "lower"]
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        let (middle, upper, lower) = donchian_channels(input.prices, params.length("period"));
+        vec![middle, upper, lower]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::indicator::resolve_params;
+    use crate::functions::test_data::{adpl, assert_close};
+    use parser_core::ast::FunctionArg;
+
+    #[test]
+    fn atr_matches_reference() {
+        let atr = atr(&adpl(), 5);
+        assert_eq!(atr.len(), 19);
+        assert_close(&atr[0], "2015-01-13", 1.9380000000000024);
+        assert_close(&atr[1], "2015-01-14", 1.6804000000000001);
+        assert_close(&atr[18], "2015-02-09", 2.716077268663198);
+    }
+
+    #[test]
+    fn natr_matches_reference() {
+        let natr = natr(&adpl(), 5);
+        assert_eq!(natr.len(), 19);
+        assert_close(&natr[0], "2015-01-13", 2.1191908146528182);
+        assert_close(&natr[1], "2015-01-14", 1.8265217391304351);
+        assert_close(&natr[18], "2015-02-09", 3.4848309836581963);
+    }
+
+    #[test]
+    fn hv_matches_reference() {
+        let hv = hv(&adpl(), 5);
+        assert_eq!(hv.len(), 19);
+        assert_close(&hv[0], "2015-01-13", 16.210689034086766);
+        assert_close(&hv[1], "2015-01-14", 18.314685287389263);
+        assert_close(&hv[18], "2015-02-09", 33.16822543116825);
+    }
+
+    #[test]
+    fn hv_needs_two_returns() {
+        let err = resolve_params(&Hv, &[FunctionArg::Number(1.0)]).err().unwrap();
+        assert!(err.contains("at least 2"), "{}", err);
+        assert!(resolve_params(&Hv, &[FunctionArg::Number(2.0)]).is_ok());
+    }
+
+    #[test]
+    fn keltner_channels_match_reference() {
+        let (middle, upper, lower) = keltner_channels(&adpl(), 5, 2.0);
+        assert_eq!((middle.len(), upper.len(), lower.len()), (19, 19, 19));
+        assert_close(&middle[0], "2015-01-13", 92.95533333333334);
+        assert_close(&upper[0], "2015-01-13", 96.83133333333335);
+        assert_close(&lower[0], "2015-01-13", 89.07933333333334);
+        assert_close(&middle[18], "2015-02-09", 81.27071447014083);
+        assert_close(&upper[18], "2015-02-09", 86.70286900746723);
+        assert_close(&lower[18], "2015-02-09", 75.83855993281443);
+    }
+
+    #[test]
+    fn donchian_channels_match_reference() {
+        let (middle, upper, lower) = donchian_channels(&adpl(), 5);
+        assert_eq!((middle.len(), upper.len(), lower.len()), (20, 20, 20));
+        assert_close(&upper[0], "2015-01-12", 95.0);
+        assert_close(&lower[0], "2015-01-12", 91.02);
+        assert_close(&middle[0], "2015-01-12", (95.0 + 91.02) / 2.0);
+        assert_close(&upper[19], "2015-02-09", 84.47);
+        assert_close(&lower[19], "2015-02-09", 77.01);
+        assert_close(&middle[19], "2015-02-09", (84.47 + 77.01) / 2.0);
+    }
+}
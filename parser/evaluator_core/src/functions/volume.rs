@@ -0,0 +1,387 @@
+use crate::functions::indicator::{Indicator, IndicatorInput, Panel, ParamSpec, Params, Series};
+use crate::functions::volatility::bar_range;
+use crate::types::DailyPrice;
+
+// (date, prices) of a day with the bar carrying its volume and turnover
+type VolumeBar<'a> = (&'a (String, (f64, f64, f64, f64)), &'a DailyPrice);
+
+// bars of a stock paired with their prices, None for indexes and derived items that have no volume
+fn volume_bars<'a>(input: &IndicatorInput<'a>) -> Option<Vec<VolumeBar<'a>>> {
+    if input.bars.is_empty() || input.bars.len() != input.prices.len() {
+        return None;
+    }
+    Some(input.prices.iter().zip(input.bars).collect())
+}
+
+// (high + low + close) / 3
+fn typical_price(bar: &(f64, f64, f64, f64)) -> f64 {
+    let (high, low) = bar_range(bar);
+    (high + low + bar.0) / 3.0
+}
+
+// turnover of the day, volume at the average price when the exchange didn't report it
+fn turnover(price: &(f64, f64, f64, f64), bar: &DailyPrice) -> f64 {
+    bar.turnover
+        .unwrap_or_else(|| bar.volume.unwrap_or(0.0) * bar.vwap_price.unwrap_or_else(|| typical_price(price)))
+}
+
+/// On-Balance Volume, volume added on up days and subtracted on down days.
+pub fn obv(input: &IndicatorInput) -> Series {
+    let Some(bars) = volume_bars(input) else {
+        return vec![];
+    };
+
+    let mut total = 0.0;
+    let mut result: Series = Vec::with_capacity(bars.len());
+    for (i, ((date, price), bar)) in bars.iter().enumerate() {
+        if i > 0 {
+            let prev_close = bars[i - 1].0.1.0;
+            let volume = bar.volume.unwrap_or(0.0);
+            if price.0 > prev_close {
+                total += volume;
+            } else if price.0 < prev_close {
+                total -= volume;
+            }
+        }
+        result.push((date.clone(), (total, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// Money Flow Index, a volume weighted RSI of the typical price over `period` bars.
+pub fn mfi(input: &IndicatorInput, period: usize) -> Series {
+    let Some(bars) = volume_bars(input) else {
+        return vec![];
+    };
+    if period == 0 || bars.len() <= period {
+        return vec![];
+    }
+
+    // signed money flow of every bar from the second one on
+    let flows: Vec<f64> = bars
+        .windows(2)
+        .map(|w| {
+            let prev = typical_price(&w[0].0.1);
+            let typical = typical_price(&w[1].0.1);
+            let flow = typical * w[1].1.volume.unwrap_or(0.0);
+            if typical > prev {
+                flow
+            } else if typical < prev {
+                -flow
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let mut result: Series = Vec::with_capacity(flows.len() - period + 1);
+    for i in (period - 1)..flows.len() {
+        let window = &flows[i + 1 - period..=i];
+        let positive: f64 = window.iter().filter(|f| **f > 0.0).sum();
+        let negative: f64 = -window.iter().filter(|f| **f < 0.0).sum::<f64>();
+        let value = if negative == 0.0 { 100.0 } else { 100.0 - 100.0 / (1.0 + positive / negative) };
+        result.push((bars[i + 1].0.0.clone(), (value, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// Chaikin Money Flow, volume weighted position of the close within the day's range, between -1 and 1.
+pub fn cmf(input: &IndicatorInput, period: usize) -> Series {
+    let Some(bars) = volume_bars(input) else {
+        return vec![];
+    };
+    if period == 0 || bars.len() < period {
+        return vec![];
+    }
+
+    let (flows, volumes): (Vec<f64>, Vec<f64>) = bars
+        .iter()
+        .map(|((_, price), bar)| {
+            let (high, low) = bar_range(price);
+            let volume = bar.volume.unwrap_or(0.0);
+            let multiplier = if high > low { ((price.0 - low) - (high - price.0)) / (high - low) } else { 0.0 };
+            (multiplier * volume, volume)
+        })
+        .unzip();
+
+    let mut result: Series = Vec::with_capacity(bars.len() - period + 1);
+    for i in (period - 1)..bars.len() {
+        let volume: f64 = volumes[i + 1 - period..=i].iter().sum();
+        let flow: f64 = flows[i + 1 - period..=i].iter().sum();
+        let value = if volume > 0.0 { flow / volume } else { 0.0 };
+        result.push((bars[i].0.0.clone(), (value, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// Volume Weighted Average Price of the last `period` bars, or anchored to the first bar when `period` is 0.
+/// Uses the exchange's VWAP of each day where available and the typical price otherwise.
+pub fn vwap(input: &IndicatorInput, period: usize) -> Series {
+    let Some(bars) = volume_bars(input) else {
+        return vec![];
+    };
+    if bars.len() < period.max(1) {
+        return vec![];
+    }
+
+    let (values, volumes): (Vec<f64>, Vec<f64>) = bars
+        .iter()
+        .map(|((_, price), bar)| {
+            let volume = bar.volume.unwrap_or(0.0);
+            (bar.vwap_price.unwrap_or_else(|| typical_price(price)) * volume, volume)
+        })
+        .unzip();
+
+    let mut result: Series = Vec::with_capacity(bars.len());
+    for i in period.max(1) - 1..bars.len() {
+        let start = if period == 0 { 0 } else { i + 1 - period };
+        let volume: f64 = volumes[start..=i].iter().sum();
+        // no trades yet, the close is the best estimate
+        let value = if volume > 0.0 { values[start..=i].iter().sum::<f64>() / volume } else { bars[i].0.1.0 };
+        result.push((bars[i].0.0.clone(), (value, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// Relative volume, the day's volume divided by the average volume of the previous `period` bars.
+pub fn rvol(input: &IndicatorInput, period: usize) -> Series {
+    let Some(bars) = volume_bars(input) else {
+        return vec![];
+    };
+    if period == 0 || bars.len() <= period {
+        return vec![];
+    }
+
+    let volumes: Vec<f64> = bars.iter().map(|(_, bar)| bar.volume.unwrap_or(0.0)).collect();
+    let mut result: Series = Vec::with_capacity(bars.len() - period);
+    for i in period..bars.len() {
+        let average = volumes[i - period..i].iter().sum::<f64>() / period as f64;
+        let value = if average > 0.0 { volumes[i] / average } else { 0.0 };
+        result.push((bars[i].0.0.clone(), (value, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+/// Average daily turnover of the last `period` bars, in the turnover currency (or `currency` of the script).
+pub fn adt(input: &IndicatorInput, period: usize) -> Series {
+    let Some(bars) = volume_bars(input) else {
+        return vec![];
+    };
+    if period == 0 || bars.len() < period {
+        return vec![];
+    }
+
+    let turnovers: Vec<f64> = bars.iter().map(|((_, price), bar)| turnover(price, bar)).collect();
+    let mut result: Series = Vec::with_capacity(bars.len() - period + 1);
+    for i in (period - 1)..bars.len() {
+        let average = turnovers[i + 1 - period..=i].iter().sum::<f64>() / period as f64;
+        result.push((bars[i].0.0.clone(), (average, 0.0, 0.0, 0.0)));
+    }
+
+    result
+}
+
+
+pub struct Obv;
+
+impl Indicator for Obv {
+    fn name(&self) -> &'static str {
+        "OBV"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        &[]
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn uses_volume(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, _params: &Params) -> Vec<Series> {
+        vec![obv(input)]
+    }
+}
+
+pub struct Mfi;
+
+impl Indicator for Mfi {
+    fn name(&self) -> &'static str {
+        "MFI"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 14)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(0.0, 100.0)
+    }
+
+    fn uses_volume(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![mfi(input, params.length("period"))]
+    }
+}
+
+pub struct Cmf;
+
+impl Indicator for Cmf {
+    fn name(&self) -> &'static str {
+        "CMF"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Range(-1.0, 1.0)
+    }
+
+    fn uses_volume(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![cmf(input, params.length("period"))]
+    }
+}
+
+pub struct Vwap;
+
+impl Indicator for Vwap {
+    fn name(&self) -> &'static str {
+        "VWAP"
+    }
+
+    // 0 anchors the VWAP to the start of the date range
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 0).at_least(0)];
+        PARAMS
+    }
+
+    fn uses_volume(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![vwap(input, params.length("period"))]
+    }
+}
+
+pub struct Rvol;
+
+impl Indicator for Rvol {
+    fn name(&self) -> &'static str {
+        "RVOL"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn uses_volume(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![rvol(input, params.length("period"))]
+    }
+}
+
+pub struct Adt;
+
+impl Indicator for Adt {
+    fn name(&self) -> &'static str {
+        "ADT"
+    }
+
+    fn params(&self) -> &'static [ParamSpec] {
+        const PARAMS: &[ParamSpec] = &[ParamSpec::length("period", 20)];
+        PARAMS
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::Separate
+    }
+
+    fn uses_volume(&self) -> bool {
+        true
+    }
+
+    fn compute(&self, input: &IndicatorInput, params: &Params) -> Vec<Series> {
+        vec![adt(input, params.length("period"))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::functions::indicator::resolve_params;
+    use parser_core::ast::FunctionArg;
+
+    fn bar(date: &str, vwap_price: Option<f64>, volume: f64) -> DailyPrice {
+        DailyPrice {
+            id: 0,
+            stock_isin: "SI0031102120".to_string(),
+            date: date.to_string(),
+            trading_model_id: None,
+            open_price: None,
+            high_price: None,
+            low_price: None,
+            last_price: None,
+            vwap_price,
+            change_prev_close_percentage: None,
+            num_trades: None,
+            volume: Some(volume),
+            turnover: None,
+            price_currency: None,
+            turnover_currency: None,
+        }
+    }
+
+    // VWAP of three days: the exchange's 10.5, then the typical price 12 and 13 as it reported none
+    fn vwap_of(period: f64) -> Vec<f64> {
+        let prices: Series = vec![
+            ("2024-01-01".to_string(), (10.0, 10.0, 11.0, 9.0)),
+            ("2024-01-02".to_string(), (12.0, 12.0, 13.0, 11.0)),
+            ("2024-01-03".to_string(), (12.0, 13.0, 14.0, 13.0)),
+        ];
+        let bars = [bar("2024-01-01", Some(10.5), 100.0), bar("2024-01-02", None, 300.0), bar("2024-01-03", None, 0.0)];
+        let input = IndicatorInput { prices: &prices, actions: &[], bars: &bars, benchmark: &vec![] };
+        let params = resolve_params(&Vwap, &[FunctionArg::Number(period)]).unwrap();
+        Vwap.compute(&input, &params)[0].iter().map(|(_, v)| v.0).collect()
+    }
+
+    #[test]
+    fn vwap_uses_the_exchange_price_before_the_typical_price() {
+        // a day without trades keeps the close
+        assert_eq!(vwap_of(1.0), vec![10.5, 12.0, 12.0]);
+    }
+
+    #[test]
+    fn vwap_of_period_0_is_anchored() {
+        assert_eq!(vwap_of(0.0), vec![10.5, 11.625, 11.625]);
+        assert_eq!(vwap_of(2.0), vec![11.625, 12.0]);
+        assert!(resolve_params(&Rvol, &[FunctionArg::Number(0.0)]).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use crate::types::{CorporateAction, DailyPrice, Stock};
+
+// irrevocably fixed conversion rate for the Croatian kuna (1 EUR = 7.53450 HRK)
+pub const HRK_PER_EUR: f64 = 7.53450;
+
+/// Local FX-rate table, every rate is the amount of the currency per 1 EUR.
+#[derive(Debug, Clone)]
+pub struct FxRates {
+    rates: HashMap<String, f64>,
+}
+
+impl Default for FxRates {
+    fn default() -> Self {
+        let mut rates = HashMap::new();
+        rates.insert("EUR".to_string(), 1.0);
+        rates.insert("HRK".to_string(), HRK_PER_EUR);
+        Self { rates }
+    }
+}
+
+impl FxRates {
+    pub fn set_rate(&mut self, currency: &str, per_eur: f64) {
+        self.rates.insert(currency.to_uppercase(), per_eur);
+    }
+
+    pub fn rate(&self, currency: &str) -> Option<f64> {
+        self.rates.get(&currency.to_uppercase()).copied()
+    }
+
+    /// Factor that converts an amount in `from` into `to`, None if either rate is unknown.
+    pub fn factor(&self, from: &str, to: &str) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(1.0);
+        }
+        match (self.rate(from), self.rate(to)) {
+            (Some(from_rate), Some(to_rate)) if from_rate != 0.0 => Some(to_rate / from_rate),
+            _ => None,
+        }
+    }
+
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        self.factor(from, to).map(|f| amount * f)
+    }
+
+    /// Converts prices and turnover of each bar from its quote currency into `to`.
+    /// Bars without a currency are left as they are. Bars quoted in a currency without a rate
+    /// are dropped and turnover in one is cleared, so series never mix currencies.
+    pub fn convert_daily_prices(&self, prices: &[DailyPrice], to: &str) -> Vec<DailyPrice> {
+        let mut unknown: Vec<String> = Vec::new();
+        let converted = prices
+            .iter()
+            .cloned()
+            .filter_map(|mut p| {
+                if let Some(currency) = p.price_currency.clone() {
+                    let Some(f) = self.factor(&currency, to) else {
+                        unknown.push(currency);
+                        return None;
+                    };
+                    p.open_price = p.open_price.map(|v| v * f);
+                    p.high_price = p.high_price.map(|v| v * f);
+                    p.low_price = p.low_price.map(|v| v * f);
+                    p.last_price = p.last_price.map(|v| v * f);
+                    p.vwap_price = p.vwap_price.map(|v| v * f);
+                    p.price_currency = Some(to.to_string());
+                }
+                if let Some(currency) = p.turnover_currency.clone() {
+                    match self.factor(&currency, to) {
+                        Some(f) => p.turnover = p.turnover.map(|v| v * f),
+                        None => {
+                            unknown.push(currency);
+                            p.turnover = None;
+                        }
+                    }
+                    p.turnover_currency = Some(to.to_string());
+                }
+                Some(p)
+            })
+            .collect();
+
+        if !unknown.is_empty() {
+            unknown.sort();
+            unknown.dedup();
+            eprintln!("No FX rate for {} to {}, dropped those bars", unknown.join(", "), to);
+        }
+        converted
+    }
+
+    /// Converts dividend and subscription amounts of corporate actions into `to`.
+    pub fn convert_actions(&self, actions: &[CorporateAction], to: &str) -> Vec<CorporateAction> {
+        actions
+            .iter()
+            .cloned()
+            .map(|mut a| {
+                if let Some(f) = a.currency.as_deref().and_then(|c| self.factor(c, to)) {
+                    a.amount = a.amount.map(|v| v * f);
+                    a.currency = Some(to.to_string());
+                }
+                a
+            })
+            .collect()
+    }
+
+    /// Converts the last price of a stock, quoted in `from` (currency of its latest bar).
+    /// Without a rate the price is dropped rather than shown in the wrong currency.
+    pub fn convert_stock(&self, stock: &Stock, from: &str, to: &str) -> Stock {
+        let mut stock = stock.clone();
+        match self.factor(from, to) {
+            Some(f) => stock.last_price = stock.last_price.map(|v| v * f),
+            None => {
+                eprintln!("No FX rate for {} to {}, dropped the last price of {}", from, to, stock.symbol);
+                stock.last_price = None;
+            }
+        }
+        stock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(date: &str, close: f64, currency: &str) -> DailyPrice {
+        DailyPrice {
+            id: 0,
+            stock_isin: "HRKRKGRA0003".to_string(),
+            date: date.to_string(),
+            trading_model_id: None,
+            open_price: Some(close),
+            high_price: Some(close),
+            low_price: Some(close),
+            last_price: Some(close),
+            vwap_price: None,
+            change_prev_close_percentage: None,
+            num_trades: None,
+            volume: Some(10.0),
+            turnover: Some(close * 10.0),
+            price_currency: Some(currency.to_string()),
+            turnover_currency: Some(currency.to_string()),
+        }
+    }
+
+    #[test]
+    fn converts_kuna_bars_into_euro() {
+        let rates = FxRates::default();
+        let converted = rates.convert_daily_prices(&[price("2022-12-30", 753.45, "HRK"), price("2023-01-02", 100.0, "EUR")], "EUR");
+
+        assert_eq!(converted.len(), 2);
+        assert!((converted[0].last_price.unwrap() - 100.0).abs() < 1e-9);
+        assert!((converted[0].turnover.unwrap() - 1000.0).abs() < 1e-9);
+        assert_eq!(converted[0].price_currency.as_deref(), Some("EUR"));
+        assert_eq!(converted[1].last_price, Some(100.0));
+        assert!((rates.factor("EUR", "hrk").unwrap() - HRK_PER_EUR).abs() < 1e-12);
+    }
+
+    #[test]
+    fn drops_bars_in_a_currency_without_a_rate() {
+        let rates = FxRates::default();
+        let converted = rates.convert_daily_prices(&[price("2023-01-02", 100.0, "EUR"), price("2023-01-03", 120.0, "USD")], "EUR");
+
+        assert_eq!(converted.len(), 1);
+        assert_eq!(converted[0].date, "2023-01-02");
+        assert_eq!(rates.factor("USD", "EUR"), None);
+
+        // turnover in an unknown currency is cleared, the prices are still converted
+        let mut bar = price("2023-01-04", 753.45, "HRK");
+        bar.turnover_currency = Some("USD".to_string());
+        let converted = rates.convert_daily_prices(&[bar], "EUR");
+        assert_eq!(converted[0].turnover, None);
+        assert!((converted[0].last_price.unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn drops_the_last_price_of_a_stock_without_a_rate() {
+        let stock = Stock {
+            isin: "HRKRKGRA0003".to_string(),
+            mic: "XZAG".to_string(),
+            symbol: "KRKG".to_string(),
+            name: None,
+            nace: None,
+            sector_id: None,
+            sector_name: None,
+            first_trading_date: None,
+            quantity: None,
+            description: None,
+            logo_url: None,
+            website_url: None,
+            last_price: Some(753.45),
+            change_prev_close_percentage: None,
+        };
+        let rates = FxRates::default();
+
+        assert!((rates.convert_stock(&stock, "HRK", "EUR").last_price.unwrap() - 100.0).abs() < 1e-9);
+        assert_eq!(rates.convert_stock(&stock, "USD", "EUR").last_price, None);
+    }
+}
@@ -1,6 +1,9 @@
 use chrono::{Duration, Local, NaiveDate};
 use parser_core::ast::{FunctionArg, Expr, ArithmeticOp};
 use crate::response_types::{ChartData, TrackedItem};
+use crate::functions::functions::{call_item, function_key};
+use crate::functions::indicator::Series;
+use crate::types::{DailyPrice, IndexValue};
 
 
 pub fn get_today() -> String {
@@ -69,19 +72,40 @@ pub fn all_indexes_symbols() -> Vec<String> {
     ]
 }
 
-pub fn create_function_id(name: &String, args: &Vec<FunctionArg>, item: &String) -> String {
-    let args_str: Vec<String> = args
+// numeric and duration args of a function call, items are not part of them
+pub fn function_params(args: &Vec<FunctionArg>) -> Vec<String> {
+    args
         .iter()
         .filter_map(|arg| {
-            if let FunctionArg::Number(num) = arg {
-                Some(num.to_string())
-            } else {
-                None
+            match arg {
+                FunctionArg::Number(num) => Some(num.to_string()),
+                FunctionArg::Duration(duration) => Some(duration.clone()),
+                _ => None,
             }
         })
-        .collect();
-    
-    format!("{}_{}_{}", name, args_str.join(","), item)
+        .collect()
+}
+
+fn op_symbol(op: &ArithmeticOp) -> &'static str {
+    match op {
+        ArithmeticOp::Add => "+",
+        ArithmeticOp::Sub => "-",
+        ArithmeticOp::Div => "/",
+        ArithmeticOp::Mul => "*",
+    }
+}
+
+/// Expression arg as it appears in keys, functions without an item are applied to each tracked item (RANK_ROC_126).
+pub fn expr_label(expr: &Expr) -> String {
+    match expr {
+        Expr::FunctionCall(func_call) => match call_item(&func_call.name, &func_call.args) {
+            Some(item) => function_key(func_call, &item).id(),
+            None => function_key(func_call, "").label(),
+        },
+        Expr::BinaryOp { left, op, right } => format!("{}{}{}", expr_label(left), op_symbol(op), expr_label(right)),
+        Expr::Group(inner) => format!("({})", expr_label(inner)),
+        other => expr_to_id(other, None),
+    }
 }
 
 pub fn expr_to_id(expr: &Expr, tracked_item: Option<&TrackedItem>) -> String {
@@ -93,27 +117,13 @@ pub fn expr_to_id(expr: &Expr, tracked_item: Option<&TrackedItem>) -> String {
         Expr::BinaryOp { left, op, right } => {
             let left_id = expr_to_id(left, tracked_item);
             let right_id = expr_to_id(right, tracked_item);
-            let op_str = match op {
-                ArithmeticOp::Add => "+",
-                ArithmeticOp::Sub => "-",
-                ArithmeticOp::Div => "/",
-            };
-            format!("{}{}{}", left_id, op_str, right_id)
+            format!("{}{}{}", left_id, op_symbol(op), right_id)
         }
 
         Expr::FunctionCall(func_call) => {
-            let ids = func_call.args.iter().filter_map(|arg| {
-                if let FunctionArg::Ident(ident) = arg {
-                    Some(ident.clone())
-                } else {
-                    if let Some(tracked_item) = tracked_item {
-                        Some(tracked_item.id.clone())
-                    } else {
-                        None
-                    }
-                }
-            }).collect::<Vec<String>>();
-            create_function_id(&func_call.name, &func_call.args, ids.first().unwrap_or(&"".to_string()))
+            let item = call_item(&func_call.name, &func_call.args)
+                .or_else(|| tracked_item.filter(|_| !func_call.args.is_empty()).map(|tracked_item| tracked_item.id.clone()));
+            function_key(func_call, item.as_deref().unwrap_or("")).id()
         }
 
         Expr::Tuple(_) => {
@@ -122,6 +132,56 @@ pub fn expr_to_id(expr: &Expr, tracked_item: Option<&TrackedItem>) -> String {
     }
 }
 
+// fields of a bar usable in expressions (close >= HIGHEST(252) * 0.95)
+pub fn is_bar_field(ident: &str) -> bool {
+    matches!(ident, "close" | "open" | "high" | "low")
+}
+
+/// Fields of the item listing, a single current value rather than a series.
+pub fn is_item_field(ident: &str) -> bool {
+    matches!(ident, "price" | "change" | "market_cap" | "country" | "si" | "hr" | "at")
+}
+
+/// One field of every bar as the value (close) of a new series.
+pub fn bar_field_series(prices: &Series, field: &str) -> Series {
+    prices
+        .iter()
+        .map(|(date, (close, open, high, low))| {
+            let value = match field {
+                "open" => *open,
+                "high" => *high,
+                "low" => *low,
+                _ => *close,
+            };
+            (date.clone(), (value, value, value, value))
+        })
+        .collect()
+}
+
+pub fn daily_prices_to_series(prices: &[DailyPrice]) -> Vec<(String, (f64, f64, f64, f64))> {
+    prices
+        .iter()
+        .map(|p| (p.date.clone(), (
+            p.last_price.unwrap_or(0.0),
+            p.open_price.unwrap_or(p.last_price.unwrap_or(0.0)),
+            p.high_price.unwrap_or(0.0),
+            p.low_price.unwrap_or(0.0)
+        )))
+        .collect()
+}
+
+pub fn index_values_to_series(values: &[IndexValue]) -> Vec<(String, (f64, f64, f64, f64))> {
+    values
+        .iter()
+        .map(|p| (p.date.clone(), (
+            p.last_value.unwrap_or(0.0),
+            p.open_value.unwrap_or(0.0),
+            p.high_value.unwrap_or(0.0),
+            p.low_value.unwrap_or(0.0)
+        )))
+        .collect()
+}
+
 pub fn number_series_with_dates(from: &str, to: &str, value: f64) -> Vec<(String, (f64, f64, f64, f64))> {
     let start_date = NaiveDate::parse_from_str(from, "%Y-%m-%d").expect("Invalid start date");
     let end_date = NaiveDate::parse_from_str(to, "%Y-%m-%d").expect("Invalid end date");
@@ -11,7 +11,16 @@ pub mod functions;
 pub mod eval_sort;
 pub mod eval_filter;
 pub mod eval_plot;
+pub mod resample;
+pub mod adjust;
+pub mod fx;
+pub mod config;
+pub mod cache;
+pub mod session;
+pub mod symbols;
+pub mod series_key;
 use crate::response_types::Response;
+use crate::config::EvalConfig;
 use evaluator::evaluate_input;
 use console_error_panic_hook;
 
@@ -22,31 +31,39 @@ pub fn test_func() -> String {
     "Hello from WASM".to_string()
 }
 
+// config is an optional EvalConfig object, undefined or null uses the defaults.
+// Rejects on an invalid config and resolves to undefined if the script doesn't parse.
 #[wasm_bindgen]
-pub async fn evaluate_script_wasm(input: &str) -> Option<JsValue> {
+pub async fn evaluate_script_wasm(input: &str, config: JsValue) -> Result<JsValue, JsValue> {
     console_error_panic_hook::set_once();
+    let config: EvalConfig = if config.is_undefined() || config.is_null() {
+        EvalConfig::default()
+    } else {
+        serde_wasm_bindgen::from_value(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid config: {}", e)))?
+    };
     let res = parse_script(input);
     match res {
         Ok(ast) => {
             println!("Parsed successfully!\n{:#?}", ast);
-            let response = evaluate_ast(&ast).await;
+            let response = evaluate_ast(&ast, &config).await;
             println!("response {:?}", response);
             // serialize Response into JsValue
-            serde_wasm_bindgen::to_value(&response).ok()
+            serde_wasm_bindgen::to_value(&response).map_err(JsValue::from)
         }
         Err(e) => {
             eprintln!("Parse error:\n{}", e);
-            None
+            Ok(JsValue::UNDEFINED)
         }
     }
 }
 
-pub async fn evaluate_script(input: &str) -> Option<Response> {
+pub async fn evaluate_script(input: &str, config: &EvalConfig) -> Option<Response> {
     let res = parse_script(input);
     match res {
         Ok(ast) => {
             println!("Parsed successfully!\n{:#?}", ast);
-            let response = evaluate_ast(&ast).await;
+            let response = evaluate_ast(&ast, config).await;
             println!("response {:?}", response);
             Some(response)
         }
@@ -57,9 +74,9 @@ pub async fn evaluate_script(input: &str) -> Option<Response> {
     }
 }
 
-pub async fn evaluate_ast(ast: &Program) -> Response {
+pub async fn evaluate_ast(ast: &Program, config: &EvalConfig) -> Response {
     println!("Evaluating AST: {:#?}", ast);
-    let res = evaluate_input(&ast).await;
+    let res = evaluate_input(&ast, config).await;
     println!("Final response: {:#?}", res);
     res
 }
\ No newline at end of file
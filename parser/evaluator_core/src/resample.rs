@@ -0,0 +1,204 @@
+use chrono::{Datelike, NaiveDate};
+use crate::functions::indicator::Series;
+use crate::types::{DailyPrice, IndexValue, Timeframe};
+
+// key of the bar a date belongs to, dates with the same key are aggregated together
+pub fn bucket_key(date: &str, timeframe: Timeframe) -> (i32, u32) {
+    let d = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(d) => d,
+        Err(_) => panic!("Invalid date in price series: {}", date),
+    };
+    match timeframe {
+        Timeframe::Daily => (d.year(), d.ordinal()),
+        Timeframe::Weekly => (d.iso_week().year(), d.iso_week().week()),
+        Timeframe::Monthly => (d.year(), d.month()),
+        Timeframe::Quarterly => (d.year(), (d.month() - 1) / 3),
+    }
+}
+
+// splits date sorted rows into consecutive groups that fall into the same bar
+fn group_by_bucket<T>(rows: &[T], timeframe: Timeframe, date: impl Fn(&T) -> &str) -> Vec<&[T]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for i in 1..=rows.len() {
+        if i == rows.len() || bucket_key(date(&rows[i]), timeframe) != bucket_key(date(&rows[start]), timeframe) {
+            if i > start {
+                groups.push(&rows[start..i]);
+            }
+            start = i;
+        }
+    }
+    groups
+}
+
+fn sum_opt(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    values.fold(None, |acc, v| match (acc, v) {
+        (Some(a), Some(b)) => Some(a + b),
+        (None, v) => v,
+        (a, None) => a,
+    })
+}
+
+fn max_opt(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    values.flatten().fold(None, |acc: Option<f64>, v| Some(acc.map_or(v, |a| a.max(v))))
+}
+
+fn min_opt(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    values.flatten().fold(None, |acc: Option<f64>, v| Some(acc.map_or(v, |a| a.min(v))))
+}
+
+/// Aggregates daily stock prices into OHLCV bars (first open, max high, min low, last close,
+/// summed volume and turnover). Each bar is dated with the last trading day it contains.
+pub fn resample_daily_prices(prices: &[DailyPrice], timeframe: Timeframe) -> Vec<DailyPrice> {
+    if timeframe == Timeframe::Daily {
+        return prices.to_vec();
+    }
+
+    let mut sorted = prices.to_vec();
+    sorted.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let mut bars: Vec<DailyPrice> = Vec::new();
+    for group in group_by_bucket(&sorted, timeframe, |p| &p.date) {
+        let first = &group[0];
+        let last = &group[group.len() - 1];
+
+        let volume = sum_opt(group.iter().map(|p| p.volume));
+        let turnover = sum_opt(group.iter().map(|p| p.turnover));
+        let vwap_price = match (turnover, volume) {
+            (Some(t), Some(v)) if v > 0.0 => Some(t / v),
+            _ => last.vwap_price,
+        };
+        let change_prev_close_percentage = match (bars.last().and_then(|b| b.last_price), last.last_price) {
+            (Some(prev), Some(close)) if prev != 0.0 => Some((close / prev - 1.0) * 100.0),
+            _ => last.change_prev_close_percentage,
+        };
+        let num_trades = group.iter().filter_map(|p| p.num_trades).reduce(|a, b| a + b);
+
+        bars.push(DailyPrice {
+            id: last.id,
+            stock_isin: last.stock_isin.clone(),
+            date: last.date.clone(),
+            trading_model_id: last.trading_model_id.clone(),
+            open_price: group.iter().find_map(|p| p.open_price).or(first.last_price),
+            high_price: max_opt(group.iter().map(|p| p.high_price)),
+            low_price: min_opt(group.iter().map(|p| p.low_price)),
+            last_price: last.last_price,
+            vwap_price,
+            change_prev_close_percentage,
+            num_trades,
+            volume,
+            turnover,
+            price_currency: last.price_currency.clone(),
+            turnover_currency: last.turnover_currency.clone(),
+        });
+    }
+    bars
+}
+
+/// Aggregates daily index values into OHLC bars, same rules as `resample_daily_prices`.
+pub fn resample_index_values(values: &[IndexValue], timeframe: Timeframe) -> Vec<IndexValue> {
+    if timeframe == Timeframe::Daily {
+        return values.to_vec();
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let mut bars: Vec<IndexValue> = Vec::new();
+    for group in group_by_bucket(&sorted, timeframe, |v| &v.date) {
+        let first = &group[0];
+        let last = &group[group.len() - 1];
+
+        let change_prev_close_percentage = match (bars.last().and_then(|b| b.last_value), last.last_value) {
+            (Some(prev), Some(close)) if prev != 0.0 => Some((close / prev - 1.0) * 100.0),
+            _ => last.change_prev_close_percentage,
+        };
+
+        bars.push(IndexValue {
+            id: last.id,
+            index_isin: last.index_isin.clone(),
+            date: last.date.clone(),
+            open_value: group.iter().find_map(|v| v.open_value).or(first.last_value),
+            high_value: max_opt(group.iter().map(|v| v.high_value)),
+            low_value: min_opt(group.iter().map(|v| v.low_value)),
+            last_value: last.last_value,
+            change_prev_close_percentage,
+            turnover: sum_opt(group.iter().map(|v| v.turnover)),
+        });
+    }
+    bars
+}
+
+/// Resamples an already derived (close, open, high, low) series, used by `RESAMPLE(item, 1w)`.
+pub fn resample_series(series: &Series, timeframe: Timeframe) -> Series {
+    if timeframe == Timeframe::Daily {
+        return series.to_vec();
+    }
+
+    let mut sorted = series.to_vec();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+    group_by_bucket(&sorted, timeframe, |(d, _)| d.as_str())
+        .into_iter()
+        .map(|group| {
+            let first = &group[0];
+            let last = &group[group.len() - 1];
+            let high = group.iter().map(|(_, v)| v.2).fold(f64::MIN, f64::max);
+            let low = group.iter().map(|(_, v)| v.3).fold(f64::MAX, f64::min);
+            (last.0.clone(), (last.1.0, first.1.1, high, low))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_key_uses_iso_weeks_across_the_year_end() {
+        // 2024-12-30 and 2025-01-03 are in ISO week 1 of 2025, 2024-12-27 in week 52 of 2024
+        assert_eq!(bucket_key("2024-12-27", Timeframe::Weekly), (2024, 52));
+        assert_eq!(bucket_key("2024-12-30", Timeframe::Weekly), (2025, 1));
+        assert_eq!(bucket_key("2025-01-03", Timeframe::Weekly), (2025, 1));
+        // 2021-01-01 still belongs to week 53 of 2020
+        assert_eq!(bucket_key("2021-01-01", Timeframe::Weekly), (2020, 53));
+    }
+
+    #[test]
+    fn bucket_key_groups_months_into_quarters() {
+        assert_eq!(bucket_key("2024-01-02", Timeframe::Quarterly), (2024, 0));
+        assert_eq!(bucket_key("2024-03-29", Timeframe::Quarterly), (2024, 0));
+        assert_eq!(bucket_key("2024-04-01", Timeframe::Quarterly), (2024, 1));
+        assert_eq!(bucket_key("2024-12-31", Timeframe::Quarterly), (2024, 3));
+        assert_eq!(bucket_key("2025-01-02", Timeframe::Quarterly), (2025, 0));
+    }
+
+    #[test]
+    fn group_by_bucket_splits_consecutive_periods() {
+        let dates = ["2024-12-23", "2024-12-27", "2024-12-30", "2025-01-03", "2025-01-06"];
+        let weeks: Vec<Vec<&str>> = group_by_bucket(&dates, Timeframe::Weekly, |d| d)
+            .into_iter()
+            .map(|group| group.to_vec())
+            .collect();
+        assert_eq!(weeks, vec![vec!["2024-12-23", "2024-12-27"], vec!["2024-12-30", "2025-01-03"], vec!["2025-01-06"]]);
+
+        let quarters = group_by_bucket(&dates, Timeframe::Quarterly, |d| d);
+        assert_eq!(quarters.len(), 2);
+        assert_eq!(quarters[1], &["2025-01-03", "2025-01-06"]);
+        assert!(group_by_bucket(&[] as &[&str], Timeframe::Monthly, |d| d).is_empty());
+    }
+
+    #[test]
+    fn resample_series_keeps_first_open_and_extremes() {
+        let series: Series = vec![
+            ("2024-03-28".to_string(), (10.0, 9.0, 11.0, 8.5)),
+            ("2024-04-02".to_string(), (12.0, 10.5, 12.5, 10.0)),
+            ("2024-04-30".to_string(), (11.0, 12.0, 13.0, 9.5)),
+        ];
+        let quarters = resample_series(&series, Timeframe::Quarterly);
+        assert_eq!(quarters, vec![
+            ("2024-03-28".to_string(), (10.0, 9.0, 11.0, 8.5)),
+            ("2024-04-30".to_string(), (11.0, 10.5, 13.0, 9.5)),
+        ]);
+    }
+}
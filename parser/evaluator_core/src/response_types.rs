@@ -3,6 +3,7 @@ use serde::{Serialize, Deserialize};
 use crate::types::*;
 use std::collections::HashMap;
 use crate::types::{IndexValue, DailyPrice};
+use crate::series_key::SeriesKey;
 
 
 // MAIN RESPONSE STRUCT
@@ -17,6 +18,7 @@ pub struct Response {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchingItem {
     pub item: ResponseItem,
+    pub key: SeriesKey,
     pub extra_data: HashMap<String, ExtraValue>,
 }
 
@@ -76,6 +78,7 @@ pub struct FilterSortItem {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chart {
     pub id: String,
+    pub key: SeriesKey,
     pub data: Vec<ChartData>,
     pub chart_type: ChartType,
     pub panel_id: i32,
@@ -86,6 +89,10 @@ pub enum ChartType {
     Volume,
     Price,
     Indicator,
+    // bars around zero, e.g. the MACD histogram
+    Histogram,
+    // markers on the candles where a pattern fires (ENGULFING_BULL)
+    Marker,
     Rebase,
 }
 
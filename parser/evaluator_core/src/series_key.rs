@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use parser_core::ast::Expr;
+
+/// Key of a stored series: prices of an item, a function computed on an item
+/// or an arithmetic expression. `id()` gives the flat id the frontend shows (`RSI_14_KRKG`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SeriesKey {
+    // stock or index the series belongs to, or the id of a derived item
+    pub item: String,
+    pub function: Option<String>,
+    pub params: Vec<String>,
+    // set for expressions, different expressions can have the same id (`(A+B)/2` and `A+B/2`).
+    // Serialized as hex, JS numbers can't hold a u64
+    #[serde(with = "hex_hash")]
+    pub expr_hash: Option<u64>,
+    // output column of multi-output functions, None for the first one
+    pub output: Option<String>,
+}
+
+impl SeriesKey {
+    pub fn item(id: &str) -> Self {
+        Self {
+            item: id.to_string(),
+            function: None,
+            params: Vec::new(),
+            expr_hash: None,
+            output: None,
+        }
+    }
+
+    pub fn function(name: &str, params: Vec<String>, item: &str) -> Self {
+        Self {
+            item: item.to_string(),
+            function: Some(name.to_string()),
+            params,
+            expr_hash: None,
+            output: None,
+        }
+    }
+
+    pub fn expr(expr: &Expr, id: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        format!("{:?}", expr).hash(&mut hasher);
+        Self {
+            item: id.to_string(),
+            function: None,
+            params: Vec::new(),
+            expr_hash: Some(hasher.finish()),
+            output: None,
+        }
+    }
+
+    /// Prices of a stock or index, not computed from anything.
+    pub fn is_item(&self) -> bool {
+        self.function.is_none() && self.expr_hash.is_none()
+    }
+
+    /// Function calls without an item apply to every tracked item, the key is completed per item.
+    pub fn for_item(&self, item: &str) -> Self {
+        if !self.item.is_empty() {
+            return self.clone();
+        }
+        Self {
+            item: item.to_string(),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_output(&self, output: &str) -> Self {
+        Self {
+            output: Some(output.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Function and params without the item (`RSI_14`, `STOCH_14,3,3.d`, `OBV`), used as the extra data name.
+    pub fn label(&self) -> String {
+        let label = match &self.function {
+            Some(name) if self.params.is_empty() => name.clone(),
+            Some(name) => format!("{}_{}", name, self.params.join(",")),
+            None => self.item.clone(),
+        };
+        match &self.output {
+            Some(output) => format!("{}.{}", label, output),
+            None => label,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        match &self.function {
+            Some(_) => format!("{}_{}", self.label(), self.item),
+            None => self.item.clone(),
+        }
+    }
+}
+
+impl fmt::Display for SeriesKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+mod hex_hash {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hash: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match hash {
+            Some(hash) => serializer.serialize_some(&format!("{:016x}", hash)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(hex) => u64::from_str_radix(&hex, 16).map(Some).map_err(serde::de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use parser_core::ast::ArithmeticOp;
+    use crate::response_types::{Chart, ChartData, ChartType, Derived, MatchingItem, Response, ResponseItem};
+
+    // JS numbers are exact up to 2^53, serde_wasm_bindgen rejects larger integers
+    fn assert_safe_for_js(value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Number(n) => assert!(n.as_u64().is_none_or(|n| n <= 1 << 53), "{} is not a safe JS integer", n),
+            serde_json::Value::Array(values) => values.iter().for_each(assert_safe_for_js),
+            serde_json::Value::Object(map) => map.values().for_each(assert_safe_for_js),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn expression_keys_round_trip_through_serialization() {
+        let expr = Expr::BinaryOp {
+            left: Box::new(Expr::Ident("KRKG".to_string())),
+            op: ArithmeticOp::Div,
+            right: Box::new(Expr::Ident("PETG".to_string())),
+        };
+        let key = SeriesKey::expr(&expr, "KRKG/PETG");
+        let response = Response {
+            charts: Some(vec![Chart {
+                id: key.id(),
+                key: key.clone(),
+                data: vec![ChartData { date: "2025-01-02".to_string(), value: (1.5, 0.0, 0.0, 0.0) }],
+                chart_type: ChartType::Price,
+                panel_id: 0,
+            }]),
+            matching_items: Some(vec![MatchingItem {
+                item: ResponseItem::Derived(Derived { id: key.id() }),
+                key: key.clone(),
+                extra_data: HashMap::new(),
+            }]),
+            backtest: None,
+        };
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_safe_for_js(&json);
+        let parsed: Response = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.charts.unwrap()[0].key, key);
+        assert_eq!(parsed.matching_items.unwrap()[0].key, key);
+
+        let plain = serde_json::to_value(SeriesKey::item("KRKG")).unwrap();
+        assert_eq!(plain["expr_hash"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,101 @@
+use futures::lock::Mutex;
+use parser_core::ast::Program;
+use parser_core::parse_script;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+use wasm_bindgen_futures::js_sys::Promise;
+use crate::config::EvalConfig;
+use crate::context::EvalContext;
+use crate::evaluator::evaluate_program;
+use crate::response_types::Response;
+
+/// Evaluation context kept alive between scripts, so re-running a script after an edit
+/// reuses the stock and index listings, fetched prices and already computed indicators.
+/// Evaluations of one session run one after another.
+#[wasm_bindgen]
+pub struct EvalSession {
+    config: EvalConfig,
+    context: Rc<Mutex<EvalContext>>,
+}
+
+impl EvalSession {
+    pub fn with_config(config: EvalConfig) -> Self {
+        let context = EvalContext::with_config(&config);
+        Self {
+            config,
+            context: Rc::new(Mutex::new(context)),
+        }
+    }
+
+    pub async fn evaluate_ast(&self, ast: &Program) -> Response {
+        let mut context = self.context.lock().await;
+        evaluate_program(&mut context, ast).await
+    }
+
+    pub async fn evaluate_script(&self, input: &str) -> Option<Response> {
+        match parse_script(input) {
+            Ok(ast) => Some(self.evaluate_ast(&ast).await),
+            Err(e) => {
+                eprintln!("Parse error:\n{}", e);
+                None
+            }
+        }
+    }
+
+    /// Drops everything the session has loaded except the listings symbols are resolved with,
+    /// the shared price cache is left as it is.
+    pub async fn reset(&self) {
+        let mut context = self.context.lock().await;
+        let directory = context.directory.take();
+        *context = EvalContext::with_config(&self.config);
+        context.directory = directory;
+    }
+}
+
+#[wasm_bindgen]
+impl EvalSession {
+    // config is an optional EvalConfig object, undefined or null uses the defaults
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: JsValue) -> Result<EvalSession, JsValue> {
+        console_error_panic_hook::set_once();
+        let config: EvalConfig = if config.is_undefined() || config.is_null() {
+            EvalConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid config: {}", e)))?
+        };
+        Ok(Self::with_config(config))
+    }
+
+    /// Resolves to the response of the script, or undefined if it doesn't parse.
+    pub fn evaluate(&self, script: String) -> Promise {
+        let session = self.handle();
+        future_to_promise(async move {
+            let response = session.evaluate_script(&script).await;
+            match response {
+                Some(response) => serde_wasm_bindgen::to_value(&response).map_err(JsValue::from),
+                None => Ok(JsValue::UNDEFINED),
+            }
+        })
+    }
+
+    /// Resolves once the session is empty, waits for a running evaluation first.
+    pub fn clear(&self) -> Promise {
+        let session = self.handle();
+        future_to_promise(async move {
+            session.reset().await;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+}
+
+impl EvalSession {
+    // second handle on the same context, promises can't borrow the session
+    fn handle(&self) -> EvalSession {
+        EvalSession {
+            config: self.config.clone(),
+            context: self.context.clone(),
+        }
+    }
+}
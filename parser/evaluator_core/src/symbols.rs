@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use crate::types::{Index, Stock};
+
+/// A symbol as written in a script.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolRef {
+    // XZAG:ADPL
+    Qualified { mic: String, symbol: String },
+    Isin(String),
+    Bare(String),
+}
+
+// two letter country code, nine alphanumeric characters and a check digit
+pub fn is_isin(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() == 12
+        && bytes[..2].iter().all(|b| b.is_ascii_uppercase())
+        && bytes[2..11].iter().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && bytes[11].is_ascii_digit()
+}
+
+impl SymbolRef {
+    pub fn parse(s: &str) -> Self {
+        if let Some((mic, symbol)) = s.split_once(':') {
+            SymbolRef::Qualified { mic: mic.to_string(), symbol: symbol.to_string() }
+        } else if is_isin(s) {
+            SymbolRef::Isin(s.to_string())
+        } else {
+            SymbolRef::Bare(s.to_string())
+        }
+    }
+}
+
+/// All listed stocks and indexes, used to turn symbols of a script into item ids.
+/// Items are stored under their bare symbol when only one market lists it and as
+/// MIC:SYMBOL otherwise, so listings on different exchanges never share an id.
+/// Loaded once per session, lookups go through an index built with it.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolDirectory {
+    pub stocks: Vec<Stock>,
+    pub indexes: Vec<Index>,
+    // (mic, symbol) of the listings of every symbol and ISIN
+    by_symbol: HashMap<String, Vec<(String, String)>>,
+    by_isin: HashMap<String, Vec<(String, String)>>,
+}
+
+impl SymbolDirectory {
+    pub fn new(stocks: Vec<Stock>, indexes: Vec<Index>) -> Self {
+        let mut by_symbol: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        let mut by_isin: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        let listings = stocks
+            .iter()
+            .map(|s| (&s.mic, &s.symbol, &s.isin))
+            .chain(indexes.iter().map(|i| (&i.mic, &i.symbol, &i.isin)));
+        for (mic, symbol, isin) in listings {
+            let listing = (mic.clone(), symbol.clone());
+            by_symbol.entry(symbol.clone()).or_default().push(listing.clone());
+            by_isin.entry(isin.clone()).or_default().push(listing);
+        }
+        Self { stocks, indexes, by_symbol, by_isin }
+    }
+
+    pub fn item_id(&self, mic: &str, symbol: &str) -> String {
+        if self.by_symbol.get(symbol).is_some_and(|listings| listings.len() > 1) {
+            format!("{}:{}", mic, symbol)
+        } else {
+            symbol.to_string()
+        }
+    }
+
+    pub fn stock_id(&self, stock: &Stock) -> String {
+        self.item_id(&stock.mic, &stock.symbol)
+    }
+
+    pub fn index_id(&self, index: &Index) -> String {
+        self.item_id(&index.mic, &index.symbol)
+    }
+
+    /// Item id of a symbol, ISIN or MIC:SYMBOL. References that aren't listed are returned
+    /// as they are and left to the API, bare symbols listed on several markets are an error.
+    pub fn resolve(&self, reference: &str) -> Result<String, String> {
+        let listings = |index: &HashMap<String, Vec<(String, String)>>, key: &str| index.get(key).cloned().unwrap_or_default();
+        let matches: Vec<(String, String)> = match SymbolRef::parse(reference) {
+            SymbolRef::Qualified { mic, symbol } => listings(&self.by_symbol, &symbol)
+                .into_iter()
+                .filter(|(m, _)| m.eq_ignore_ascii_case(&mic))
+                .collect(),
+            SymbolRef::Isin(isin) => listings(&self.by_isin, &isin),
+            SymbolRef::Bare(symbol) => listings(&self.by_symbol, &symbol),
+        };
+
+        match matches.as_slice() {
+            [] => Ok(reference.to_string()),
+            [(mic, symbol)] => Ok(self.item_id(mic, symbol)),
+            _ => {
+                let options: Vec<String> = matches.iter().map(|(m, s)| format!("{}:{}", m, s)).collect();
+                Err(format!("Ambiguous symbol {}, use one of: {}", reference, options.join(", ")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stock(mic: &str, symbol: &str, isin: &str) -> Stock {
+        Stock {
+            isin: isin.to_string(),
+            mic: mic.to_string(),
+            symbol: symbol.to_string(),
+            name: None,
+            nace: None,
+            sector_id: None,
+            sector_name: None,
+            first_trading_date: None,
+            quantity: None,
+            description: None,
+            logo_url: None,
+            website_url: None,
+            last_price: None,
+            change_prev_close_percentage: None,
+        }
+    }
+
+    fn directory() -> SymbolDirectory {
+        let index = Index {
+            isin: "SI0026109882".to_string(),
+            mic: "XLJU".to_string(),
+            symbol: "SBITOP".to_string(),
+            name: None,
+            last_value: None,
+            change_prev_close_percentage: None,
+        };
+        SymbolDirectory::new(
+            vec![
+                stock("XLJU", "KRKG", "SI0031102120"),
+                stock("XZAG", "KRKG", "SI0031102120"),
+                stock("XZAG", "ADPL", "HRADPLRA0006"),
+            ],
+            vec![index],
+        )
+    }
+
+    #[test]
+    fn resolves_symbols_listed_on_one_market_to_the_bare_symbol() {
+        let directory = directory();
+        assert_eq!(directory.resolve("ADPL"), Ok("ADPL".to_string()));
+        assert_eq!(directory.resolve("HRADPLRA0006"), Ok("ADPL".to_string()));
+        assert_eq!(directory.resolve("SBITOP"), Ok("SBITOP".to_string()));
+        // unlisted symbols are left to the API
+        assert_eq!(directory.resolve("PETG"), Ok("PETG".to_string()));
+    }
+
+    #[test]
+    fn qualifies_symbols_listed_on_several_markets() {
+        let directory = directory();
+        assert_eq!(directory.resolve("xzag:KRKG"), Ok("XZAG:KRKG".to_string()));
+        assert_eq!(directory.stock_id(&directory.stocks[0]), "XLJU:KRKG");
+        assert!(directory.resolve("KRKG").unwrap_err().contains("XLJU:KRKG, XZAG:KRKG"));
+        assert!(directory.resolve("SI0031102120").is_err());
+    }
+}
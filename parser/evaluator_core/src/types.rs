@@ -1,12 +1,23 @@
 // definition of response struct
 use serde::{Serialize, Deserialize};
 
+// the API sends decimals as strings, cached histories store them as numbers
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StrOrF64 {
+    Str(String),
+    Num(f64),
+}
+
 fn str_to_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let s: Option<String> = Option::deserialize(deserializer)?;
-    Ok(s.and_then(|v| v.parse::<f64>().ok()))
+    let s: Option<StrOrF64> = Option::deserialize(deserializer)?;
+    Ok(s.and_then(|v| match v {
+        StrOrF64::Str(v) => v.parse::<f64>().ok(),
+        StrOrF64::Num(v) => Some(v),
+    }))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,8 +106,43 @@ pub struct IndexValue {
     pub turnover: Option<f64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorporateAction {
+    pub id: u64,
+    pub stock_isin: String,
+    pub ex_date: String,
+    pub action_type: String,
+    #[serde(deserialize_with = "str_to_f64")]
+    pub amount: Option<f64>,
+    #[serde(deserialize_with = "str_to_f64")]
+    pub ratio_old: Option<f64>,
+    #[serde(deserialize_with = "str_to_f64")]
+    pub ratio_new: Option<f64>,
+    pub currency: Option<String>,
+}
+
 #[derive(Debug)]
 pub enum Direction {
     Asc,
     Desc,
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeframe {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+}
+
+impl Timeframe {
+    // parses duration literals used in scripts (1d, 1w, 1m, 1q)
+    pub fn from_duration(duration: &str) -> Option<Self> {
+        match duration {
+            "1d" => Some(Timeframe::Daily),
+            "1w" => Some(Timeframe::Weekly),
+            "1m" => Some(Timeframe::Monthly),
+            "1q" => Some(Timeframe::Quarterly),
+            _ => None,
+        }
+    }
+}
@@ -64,6 +64,7 @@ pub enum ArithmeticOp {
     Add,
     Div,
     Sub,
+    Mul,
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +88,8 @@ pub enum Operand {
     Ident(String),
     FunctionCall(FunctionCall),
     LogicalExpr(Box<LogicalExpr>),
+    // operands with arithmetic, single numbers, idents and calls use the variants above
+    ArithmeticExpr(Expr),
 }
 
 #[derive(Debug, Clone)]
@@ -109,6 +112,8 @@ pub enum Comparator {
 pub struct FunctionCall {
     pub name: String,
     pub args: Vec<FunctionArg>,
+    // output of a multi-output function (`hist` in MACD(12,26,9).hist)
+    pub output: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -116,6 +121,8 @@ pub enum FunctionArg {
     Ident(String),
     Number(f64),
     String(String),
+    Duration(String),
+    Expr(Expr),
 }
 
 #[derive(Debug, Clone)]
@@ -59,10 +59,76 @@ pub fn parse_script(input: &str) -> Result<Program, &'static str> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ast::{ArithmeticOp, Comparator, Expr, LogicalExpr, Operand, Value};
+
+    // the single condition of a FILTER command
+    fn condition(conditions: &str) -> LogicalExpr {
+        let program = parse_script(&format!("FILTER(items=[stocks], conditions=[{}])", conditions)).unwrap();
+        match &program.commands[0] {
+            Command::Filter(args) => match &args.iter().find(|arg| arg.name == "conditions").unwrap().value {
+                Value::LogicalExpr(expr) => expr.clone(),
+                Value::List(values) => match &values[0] {
+                    Value::LogicalExpr(expr) => expr.clone(),
+                    other => panic!("Expected a condition, got {:?}", other),
+                },
+                other => panic!("Expected a condition, got {:?}", other),
+            },
+            other => panic!("Expected FILTER, got {:?}", other),
+        }
+    }
+
+    // left side of a `<expr> > 0` condition, with every binary operation in parentheses
+    fn left_expr(expr: &str) -> String {
+        match condition(&format!("{} > 0", expr)) {
+            LogicalExpr::Comparison { left: Operand::ArithmeticExpr(expr), .. } => show(&expr),
+            other => panic!("Expected an arithmetic comparison, got {:?}", other),
+        }
+    }
+
+    fn show(expr: &Expr) -> String {
+        match expr {
+            Expr::Number(value) => value.to_string(),
+            Expr::Ident(ident) => ident.clone(),
+            Expr::FunctionCall(call) => call.name.clone(),
+            Expr::BinaryOp { left, op, right } => {
+                let op = match op {
+                    ArithmeticOp::Add => "+",
+                    ArithmeticOp::Sub => "-",
+                    ArithmeticOp::Mul => "*",
+                    ArithmeticOp::Div => "/",
+                };
+                format!("({} {} {})", show(left), op, show(right))
+            }
+            Expr::Group(inner) => show(inner),
+            Expr::Tuple(_) => "tuple".to_string(),
+        }
+    }
 
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn multiplication_binds_tighter_than_subtraction() {
+        assert_eq!(left_expr("MA(20) - 2 * STDEV(20)"), "(MA - (2 * STDEV))");
+        assert_eq!(left_expr("MA(20) * 2 - STDEV(20) / 4"), "((MA * 2) - (STDEV / 4))");
+    }
+
+    #[test]
+    fn same_level_operators_apply_left_to_right() {
+        assert_eq!(left_expr("10 - 2 - 3"), "((10 - 2) - 3)");
+        assert_eq!(left_expr("12 / 2 * 3"), "((12 / 2) * 3)");
+    }
+
+    #[test]
+    fn minus_without_spaces_is_subtraction() {
+        assert_eq!(left_expr("close-1"), "(close - 1)");
+    }
+
+    #[test]
+    fn negative_number_operand() {
+        match condition("DRAWDOWN() < -20") {
+            LogicalExpr::Comparison { left: Operand::FunctionCall(call), op: Comparator::Lt, right: Operand::Number(value) } => {
+                assert_eq!(call.name, "DRAWDOWN");
+                assert_eq!(value, -20.0);
+            }
+            other => panic!("Expected DRAWDOWN() < -20, got {:?}", other),
+        }
     }
 }
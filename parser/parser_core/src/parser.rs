@@ -71,26 +71,17 @@ fn parse_arg_value(pair: Pair<Rule>) -> Value {
 
 //-- ARITHMETIC EXPRESSION PARSING --
 fn parse_arithmetic_expr(pair: Pair<Rule>) -> Expr {
-    let mut expr = None;
-    let mut op = None;
+    let mut terms = Vec::new();
+    let mut ops = Vec::new();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::arithmetic_term => {
-                let term_expr = parse_arithmetic_term(inner_pair);
-                if expr.is_none() {
-                    expr = Some(term_expr);
-                } else {
-                    expr = Some(Expr::BinaryOp {
-                        left: Box::new(expr.unwrap()),
-                        op: op.take().unwrap(),
-                        right: Box::new(term_expr),
-                    });
-                }
-            }
+            Rule::arithmetic_term => terms.push(parse_arithmetic_term(inner_pair)),
             Rule::operation => {
-                op = Some(match inner_pair.as_str() {
+                ops.push(match inner_pair.as_str() {
                     "+" => ArithmeticOp::Add,
+                    "-" => ArithmeticOp::Sub,
+                    "*" => ArithmeticOp::Mul,
                     "/" => ArithmeticOp::Div,
                     _ => panic!("Unknown arithmetic op"),
                 });
@@ -99,7 +90,38 @@ fn parse_arithmetic_expr(pair: Pair<Rule>) -> Expr {
         }
     }
 
-    expr.unwrap()
+    // * and / first, then + and -, operators of the same level apply left to right
+    let mut terms = terms.into_iter();
+    let mut sums = vec![terms.next().unwrap()];
+    let mut sum_ops = Vec::new();
+    for (op, term) in ops.into_iter().zip(terms) {
+        match op {
+            ArithmeticOp::Mul | ArithmeticOp::Div => {
+                let left = sums.pop().unwrap();
+                sums.push(Expr::BinaryOp {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(term),
+                });
+            }
+            ArithmeticOp::Add | ArithmeticOp::Sub => {
+                sum_ops.push(op);
+                sums.push(term);
+            }
+        }
+    }
+
+    let mut sums = sums.into_iter();
+    let mut expr = sums.next().unwrap();
+    for (op, term) in sum_ops.into_iter().zip(sums) {
+        expr = Expr::BinaryOp {
+            left: Box::new(expr),
+            op,
+            right: Box::new(term),
+        };
+    }
+
+    expr
 }
 
 fn parse_arithmetic_term(pair: Pair<Rule>) -> Expr {
@@ -192,9 +214,12 @@ fn parse_comparison(pair: Pair<Rule>) -> LogicalExpr {
 fn parse_operand(pair: Pair<Rule>) -> Operand {
     let inner = pair.into_inner().next().unwrap();
     match inner.as_rule() {
-        Rule::function_call => Operand::FunctionCall(parse_function_call(inner)),
-        Rule::ident => Operand::Ident(inner.as_str().to_string()),
-        Rule::number => Operand::Number(inner.as_str().parse::<f64>().unwrap()),
+        Rule::arithmetic_expr => match parse_arithmetic_expr(inner) {
+            Expr::FunctionCall(func_call) => Operand::FunctionCall(func_call),
+            Expr::Ident(ident) => Operand::Ident(ident),
+            Expr::Number(num) => Operand::Number(num),
+            expr => Operand::ArithmeticExpr(expr),
+        },
         Rule::logical_expr => Operand::LogicalExpr(Box::new(parse_logical_expr(inner))),
         _ => panic!("Unknown operand inner: {:?}", inner.as_rule()),
     }
@@ -219,29 +244,40 @@ fn parse_list(pair: Pair<Rule>) -> Vec<Value> {
 fn parse_function_call(pair: Pair<Rule>) -> FunctionCall {
     let mut name = "";
     let mut args = Vec::new();
+    let mut output = None;
 
     let mut inner_pairs = pair.into_inner();
     if let Some(name_pair) = inner_pairs.next() {
         name = name_pair.as_str();
     }
 
-    if let Some(args_pair) = inner_pairs.next() {
-        if args_pair.as_rule() == Rule::arguments {
-            for arg in args_pair.into_inner() {
-                for actual in arg.into_inner() {
-                    match actual.as_rule() {
-                        Rule::ident => args.push(FunctionArg::Ident(actual.as_str().to_string())),
-                        Rule::number => args.push(FunctionArg::Number(actual.as_str().parse::<f64>().unwrap())),
-                        Rule::string => args.push(FunctionArg::String(actual.as_str().to_string())),
-                        _ => panic!("Unknown function arg"),
+    for inner_pair in inner_pairs {
+        match inner_pair.as_rule() {
+            Rule::arguments => {
+                for arg in inner_pair.into_inner() {
+                    for actual in arg.into_inner() {
+                        match actual.as_rule() {
+                            Rule::ident => args.push(FunctionArg::Ident(actual.as_str().to_string())),
+                            Rule::number => args.push(FunctionArg::Number(actual.as_str().parse::<f64>().unwrap())),
+                            Rule::string => args.push(FunctionArg::String(actual.as_str().to_string())),
+                            Rule::duration => args.push(FunctionArg::Duration(actual.as_str().to_string())),
+                            Rule::expr_argument => {
+                                let expr = actual.into_inner().next().unwrap();
+                                args.push(FunctionArg::Expr(parse_arithmetic_expr(expr)));
+                            }
+                            _ => panic!("Unknown function arg"),
+                        }
                     }
                 }
             }
+            Rule::output_field => output = Some(inner_pair.as_str().to_string()),
+            _ => panic!("Unknown function call part: {:?}", inner_pair.as_rule()),
         }
     }
 
     FunctionCall {
         name: name.to_string(),
         args,
+        output,
     }
 }
@@ -74,3 +74,16 @@ CREATE TABLE index_values (
     turnover NUMERIC(20, 2),
     CONSTRAINT unique_index_date UNIQUE (index_isin, date)
 );
+
+-- Table: corporate_actions
+CREATE TABLE corporate_actions (
+    id SERIAL PRIMARY KEY,
+    stock_isin CHAR(12) REFERENCES stocks(isin) ON DELETE CASCADE,
+    ex_date DATE NOT NULL,
+    action_type VARCHAR(10) NOT NULL,  -- dividend | split | rights
+    amount NUMERIC(15, 6),             -- gross dividend per share, or subscription price for rights
+    ratio_old NUMERIC(15, 6),          -- shares held before split / per rights allotment
+    ratio_new NUMERIC(15, 6),          -- shares held after split / new shares offered
+    currency CHAR(3),
+    CONSTRAINT unique_stock_action UNIQUE (stock_isin, ex_date, action_type)
+);